@@ -0,0 +1,58 @@
+//! Measures the cost of cloning a large context.
+//!
+//! Run with `cargo bench --bench clone`.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use gtmpl_value::Value;
+
+const ROWS: usize = 100_000;
+const ITERATIONS: u32 = 100;
+
+fn context() -> Value {
+    let rows: Vec<Value> = (0..ROWS)
+        .map(|i| {
+            let mut row = HashMap::new();
+            row.insert("id".to_owned(), Value::from(i));
+            row.insert("name".to_owned(), Value::from(format!("row {}", i)));
+            row.insert("tags".to_owned(), Value::from(vec!["a", "b", "c"]));
+            row.into()
+        })
+        .collect();
+    let mut ctx = HashMap::new();
+    ctx.insert("rows".to_owned(), Value::from(rows));
+    ctx.into()
+}
+
+/// Copies every string and collection, like cloning used to.
+fn deep_copy(val: &Value) -> Value {
+    match *val {
+        Value::String(ref s) => Value::from(s.to_string()),
+        Value::Array(ref a) => Value::from(a.iter().map(deep_copy).collect::<Vec<_>>()),
+        Value::Map(ref m) | Value::Object(ref m) => Value::from(
+            m.iter()
+                .map(|(k, v)| (k.clone(), deep_copy(v)))
+                .collect::<HashMap<_, _>>(),
+        ),
+        ref v => v.clone(),
+    }
+}
+
+fn measure<F: Fn() -> Value>(f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let ctx = context();
+    let clone = measure(|| ctx.clone());
+    let copy = measure(|| deep_copy(&ctx));
+    println!("context with {} rows", ROWS);
+    println!("clone:     {:>12?} per iteration", clone);
+    println!("deep copy: {:>12?} per iteration", copy);
+}
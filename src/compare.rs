@@ -0,0 +1,311 @@
+//! Comparison of `Value`s following Go's `eq`, `ne`, `lt`, `le`, `gt` and `ge`
+//! template builtins.
+//!
+//! Only basic kinds (booleans, numbers and strings) can be ordered. Numbers
+//! compare by their exact values, regardless of their sign or kind. Unlike
+//! Go, comparing an integer with a float is not an error: `Number::from(f64)`
+//! turns integral floats into integers, so `2.0` and `1.5` must stay
+//! comparable. `Nil` and `NoValue` are equal to each other and unequal to
+//! anything else. Objects compare field by field like Go structs: objects
+//! with different fields are incompatible and a field holding a map, array
+//! or function makes them non-comparable.
+
+use std::cmp::Ordering;
+use thiserror::Error;
+
+use crate::value::Value;
+
+/// Errors returned when comparing `Value`s.
+///
+/// The messages match the ones of Go's `text/template`.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum CompareError {
+    #[error("missing argument for comparison")]
+    MissingArgument,
+    #[error("invalid type for comparison")]
+    BadComparisonType,
+    #[error("incompatible types for comparison")]
+    BadComparison,
+    #[error("non-comparable type {0}")]
+    NonComparable(&'static str),
+}
+
+fn is_invalid(val: &Value) -> bool {
+    matches!(*val, Value::NoValue | Value::Nil)
+}
+
+fn eq_one(a: &Value, b: &Value) -> Result<bool, CompareError> {
+    match (a, b) {
+        (a, b) if is_invalid(a) || is_invalid(b) => Ok(is_invalid(a) && is_invalid(b)),
+        (Value::Bool(a), Value::Bool(b)) => Ok(a == b),
+        (Value::String(a), Value::String(b)) => Ok(a == b),
+        (Value::Number(a), Value::Number(b)) => Ok(a == b),
+        (Value::Object(a), Value::Object(b)) => {
+            if a.len() != b.len() || a.keys().ne(b.keys()) {
+                return Err(CompareError::BadComparison);
+            }
+            // Like Go, check every field so non-comparable ones always error.
+            a.values()
+                .zip(b.values())
+                .try_fold(true, |eq, (a, b)| Ok(eq_one(a, b)? && eq))
+        }
+        (Value::Map(_), Value::Map(_))
+        | (Value::Array(_), Value::Array(_))
+        | (Value::Function(_), Value::Function(_)) => {
+            Err(CompareError::NonComparable(a.type_name()))
+        }
+        _ => Err(CompareError::BadComparison),
+    }
+}
+
+fn cmp(a: &Value, b: &Value) -> Result<Option<Ordering>, CompareError> {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => Ok(Some(a.cmp(b))),
+        (Value::Number(a), Value::Number(b)) => Ok(a.partial_cmp(b)),
+        (Value::String(_), Value::Number(_)) | (Value::Number(_), Value::String(_)) => {
+            Err(CompareError::BadComparison)
+        }
+        _ => Err(CompareError::BadComparisonType),
+    }
+}
+
+/// Go's `eq`: returns whether `arg1` equals any of `args`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::compare::eq;
+/// use gtmpl_value::Value;
+///
+/// let x: Value = 2.into();
+/// assert_eq!(eq(&x, &[1.into(), 2.into()]), Ok(true));
+/// assert!(eq(&x, &["2".into()]).is_err());
+/// ```
+pub fn eq(arg1: &Value, args: &[Value]) -> Result<bool, CompareError> {
+    if args.is_empty() {
+        return Err(CompareError::MissingArgument);
+    }
+    for arg in args {
+        if eq_one(arg1, arg)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Go's `ne`: returns whether `arg1` does not equal `arg2`.
+pub fn ne(arg1: &Value, arg2: &Value) -> Result<bool, CompareError> {
+    eq_one(arg1, arg2).map(|eq| !eq)
+}
+
+/// Go's `lt`: returns whether `arg1` is less than `arg2`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::compare::lt;
+/// use gtmpl_value::{CompareError, Value};
+///
+/// assert_eq!(lt(&(-1).into(), &1u64.into()), Ok(true));
+/// assert_eq!(lt(&"a".into(), &"b".into()), Ok(true));
+/// assert_eq!(lt(&true.into(), &false.into()), Err(CompareError::BadComparisonType));
+/// ```
+pub fn lt(arg1: &Value, arg2: &Value) -> Result<bool, CompareError> {
+    cmp(arg1, arg2).map(|o| o == Some(Ordering::Less))
+}
+
+/// Go's `le`: returns whether `arg1` is less than or equal to `arg2`.
+pub fn le(arg1: &Value, arg2: &Value) -> Result<bool, CompareError> {
+    Ok(lt(arg1, arg2)? || eq_one(arg1, arg2)?)
+}
+
+/// Go's `gt`: returns whether `arg1` is greater than `arg2`.
+pub fn gt(arg1: &Value, arg2: &Value) -> Result<bool, CompareError> {
+    le(arg1, arg2).map(|le| !le)
+}
+
+/// Go's `ge`: returns whether `arg1` is greater than or equal to `arg2`.
+pub fn ge(arg1: &Value, arg2: &Value) -> Result<bool, CompareError> {
+    lt(arg1, arg2).map(|lt| !lt)
+}
+
+impl Value {
+    /// Compares two `Value`s like Go's `eq`.
+    pub fn go_eq(&self, other: &Value) -> Result<bool, CompareError> {
+        eq_one(self, other)
+    }
+
+    /// Compares two `Value`s like Go's `ne`.
+    pub fn go_ne(&self, other: &Value) -> Result<bool, CompareError> {
+        ne(self, other)
+    }
+
+    /// Compares two `Value`s like Go's `lt`.
+    pub fn go_lt(&self, other: &Value) -> Result<bool, CompareError> {
+        lt(self, other)
+    }
+
+    /// Compares two `Value`s like Go's `le`.
+    pub fn go_le(&self, other: &Value) -> Result<bool, CompareError> {
+        le(self, other)
+    }
+
+    /// Compares two `Value`s like Go's `gt`.
+    pub fn go_gt(&self, other: &Value) -> Result<bool, CompareError> {
+        gt(self, other)
+    }
+
+    /// Compares two `Value`s like Go's `ge`.
+    pub fn go_ge(&self, other: &Value) -> Result<bool, CompareError> {
+        ge(self, other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::FuncError;
+    use crate::value::{Map, Number};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_eq() {
+        let one: Value = 1.into();
+        assert_eq!(one.go_eq(&1u8.into()), Ok(true));
+        assert_eq!(one.go_eq(&2.into()), Ok(false));
+        assert_eq!(Value::from(-1).go_eq(&u64::MAX.into()), Ok(false));
+        assert_eq!(Value::from(1.5).go_eq(&1.5.into()), Ok(true));
+        assert_eq!(Value::from("a").go_eq(&"a".into()), Ok(true));
+        assert_eq!(Value::from(true).go_eq(&false.into()), Ok(false));
+        assert_eq!(Value::from(f64::NAN).go_eq(&f64::NAN.into()), Ok(false));
+    }
+
+    #[test]
+    fn test_eq_nil() {
+        assert_eq!(Value::Nil.go_eq(&Value::Nil), Ok(true));
+        assert_eq!(Value::Nil.go_eq(&Value::NoValue), Ok(true));
+        assert_eq!(Value::Nil.go_eq(&1.into()), Ok(false));
+        assert_eq!(Value::from("").go_eq(&Value::NoValue), Ok(false));
+    }
+
+    #[test]
+    fn test_eq_errors() {
+        let one: Value = 1.into();
+        assert_eq!(one.go_eq(&"1".into()), Err(CompareError::BadComparison));
+        assert_eq!(eq(&one, &[]), Err(CompareError::MissingArgument));
+        let a: Value = vec![1].into();
+        assert_eq!(
+            a.go_eq(&a),
+            Err(CompareError::NonComparable("[]interface {}"))
+        );
+        let m = Value::Map(Arc::new(Map::new()));
+        assert_eq!(
+            m.go_eq(&m).unwrap_err().to_string(),
+            "non-comparable type map[string]interface {}"
+        );
+        assert_eq!(m.go_eq(&a), Err(CompareError::BadComparison));
+    }
+
+    #[test]
+    fn test_eq_objects() {
+        use crate::value::map;
+
+        let obj = |entries: &[(&str, Value)]| Value::Object(Arc::new(map(entries)));
+        let a = obj(&[("a", 1.into()), ("b", "x".into())]);
+        assert_eq!(a.go_eq(&a), Ok(true));
+        assert_eq!(
+            a.go_eq(&obj(&[("a", 1.into()), ("b", "y".into())])),
+            Ok(false)
+        );
+        assert_eq!(
+            a.go_eq(&obj(&[("a", 1.into())])),
+            Err(CompareError::BadComparison)
+        );
+        let nested = obj(&[("a", 2.into()), ("b", vec![1].into())]);
+        assert_eq!(
+            nested.go_eq(&nested),
+            Err(CompareError::NonComparable("[]interface {}"))
+        );
+        let inner = obj(&[("a", a.clone())]);
+        assert_eq!(inner.go_eq(&inner), Ok(true));
+    }
+
+    #[test]
+    fn test_eq_multiple() {
+        let x: Value = "b".into();
+        assert_eq!(eq(&x, &["a".into(), "b".into()]), Ok(true));
+        assert_eq!(eq(&x, &["a".into(), "c".into()]), Ok(false));
+        // Go stops at the first match, later arguments are not checked.
+        assert_eq!(eq(&x, &["b".into(), 1.into()]), Ok(true));
+        assert_eq!(
+            eq(&x, &["a".into(), 1.into()]),
+            Err(CompareError::BadComparison)
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let (a, b): (Value, Value) = ((-2).into(), 3u64.into());
+        assert_eq!(a.go_lt(&b), Ok(true));
+        assert_eq!(a.go_le(&b), Ok(true));
+        assert_eq!(a.go_gt(&b), Ok(false));
+        assert_eq!(a.go_ge(&b), Ok(false));
+        assert_eq!(b.go_ge(&b), Ok(true));
+        assert_eq!(b.go_le(&b), Ok(true));
+        assert_eq!(b.go_ne(&a), Ok(true));
+        assert_eq!(Value::from(1.5).go_lt(&2.5.into()), Ok(true));
+        assert_eq!(Value::from("ab").go_lt(&"b".into()), Ok(true));
+    }
+
+    #[test]
+    fn test_ordering_errors() {
+        assert_eq!(
+            Value::from(1).go_lt(&"1".into()),
+            Err(CompareError::BadComparison)
+        );
+        assert_eq!(
+            Value::from(true).go_lt(&true.into()),
+            Err(CompareError::BadComparisonType)
+        );
+        assert_eq!(
+            Value::Nil.go_lt(&1.into()),
+            Err(CompareError::BadComparisonType)
+        );
+        assert_eq!(
+            Value::from(vec![1]).go_ge(&vec![1].into()),
+            Err(CompareError::BadComparisonType)
+        );
+    }
+
+    #[test]
+    fn test_int_and_float() {
+        assert_eq!(Value::from(2.0).go_eq(&1.5.into()), Ok(false));
+        assert_eq!(Value::from(2.0).go_eq(&2.0.into()), Ok(true));
+        assert_eq!(Value::from(1.5).go_lt(&2.0.into()), Ok(true));
+        assert_eq!(Value::from(2.0).go_gt(&1.5.into()), Ok(true));
+        assert_eq!(
+            Value::from(1).go_eq(&Value::Number(Number::float(1.0))),
+            Ok(true)
+        );
+        assert_eq!(Value::from(1).go_lt(&1.5.into()), Ok(true));
+        assert_eq!(Value::from(u64::MAX).go_lt(&1e20.into()), Ok(true));
+        assert_eq!(Value::from(f64::NAN).go_lt(&1.into()), Ok(false));
+    }
+
+    #[test]
+    fn test_func_error() {
+        let err: FuncError = CompareError::BadComparison.into();
+        assert_eq!(err.to_string(), "incompatible types for comparison");
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal() {
+        let d: Value = rust_decimal::Decimal::new(150, 2).into();
+        assert_eq!(d.go_eq(&1.5.into()), Ok(true));
+        let tenth: Value = rust_decimal::Decimal::new(1, 1).into();
+        assert_eq!(tenth.go_eq(&0.1.into()), Ok(true));
+        assert_eq!(d.go_lt(&2.into()), Ok(true));
+        assert_eq!(Value::from(1).go_lt(&d), Ok(true));
+    }
+}
@@ -0,0 +1,767 @@
+//! Fallible conversion from `Value` reporting why a conversion failed.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::value::{Map, Number, Value};
+
+/// One step of the path to a nested `Value`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Index into an array.
+    Index(usize),
+    /// Key of a map or field of an object.
+    Key(String),
+}
+
+/// Error returned by `TryFromValue`.
+///
+/// `path` points to the nested value that failed to convert, e.g.
+/// `args[1].tags[3]`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{try_from_arg, Value};
+/// use std::collections::HashMap;
+///
+/// let mut m = HashMap::new();
+/// m.insert("tags".to_owned(), Value::from(vec![Value::from("a"), 1.into()]));
+/// let args: Vec<Value> = vec![1.into(), m.into()];
+/// let err = try_from_arg::<HashMap<String, Vec<String>>>(&args, 1).unwrap_err();
+/// assert_eq!(err.to_string(), "args[1].tags[1]: expected string, found int");
+/// ```
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub struct ConversionError {
+    /// Description of the expected type.
+    pub expected: &'static str,
+    /// Description of the value found instead.
+    pub found: String,
+    /// Path to the value that failed to convert, outermost first.
+    pub path: Vec<PathSegment>,
+}
+
+impl ConversionError {
+    /// Creates an error for `val` which is not of the `expected` type.
+    pub fn new(expected: &'static str, val: &Value) -> ConversionError {
+        ConversionError {
+            expected,
+            found: val.type_name().to_owned(),
+            path: vec![],
+        }
+    }
+
+    /// Creates an error for a number which is out of range for `expected`.
+    pub fn out_of_range(expected: &'static str, n: &Number) -> ConversionError {
+        ConversionError {
+            expected,
+            found: format!("{} {}", n.type_name(), n),
+            path: vec![],
+        }
+    }
+
+    /// Prepends an array index to the path.
+    pub fn at_index(mut self, index: usize) -> ConversionError {
+        self.path.insert(0, PathSegment::Index(index));
+        self
+    }
+
+    /// Prepends a map key or field name to the path.
+    pub fn at_key<S: Into<String>>(mut self, key: S) -> ConversionError {
+        self.path.insert(0, PathSegment::Key(key.into()));
+        self
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.path.iter().enumerate() {
+            match *segment {
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(ref key) if i == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(ref key) => write!(f, ".{}", key)?,
+            }
+        }
+        if !self.path.is_empty() {
+            write!(f, ": ")?;
+        }
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+/// Fallible conversion from `Value`.
+pub trait TryFromValue: Sized {
+    /// Tries to retrieve `Self` from `Value`.
+    fn try_from_value(val: &Value) -> Result<Self, ConversionError>;
+}
+
+/// `TryFromValue` as a function.
+///
+/// # Examples:
+///
+/// ```rust
+/// use gtmpl_value::{try_from_value, Value};
+///
+/// let v: Value = 1.into();
+/// assert_eq!(try_from_value::<i64>(&v), Ok(1));
+/// assert!(try_from_value::<String>(&v).is_err());
+/// ```
+pub fn try_from_value<T>(val: &Value) -> Result<T, ConversionError>
+where
+    T: TryFromValue,
+{
+    T::try_from_value(val)
+}
+
+/// Converts the `index`th function argument, reporting it as `args[index]`.
+pub fn try_from_arg<T>(args: &[Value], index: usize) -> Result<T, ConversionError>
+where
+    T: TryFromValue,
+{
+    let val = args.get(index).unwrap_or(&Value::NoValue);
+    T::try_from_value(val).map_err(|e| e.at_index(index).at_key("args"))
+}
+
+fn number<'a>(expected: &'static str, val: &'a Value) -> Result<&'a Number, ConversionError> {
+    match *val {
+        Value::Number(ref n) => Ok(n),
+        _ => Err(ConversionError::new(expected, val)),
+    }
+}
+
+impl TryFromValue for Value {
+    fn try_from_value(val: &Value) -> Result<Value, ConversionError> {
+        Ok(val.clone())
+    }
+}
+
+impl TryFromValue for bool {
+    fn try_from_value(val: &Value) -> Result<bool, ConversionError> {
+        match *val {
+            Value::Bool(b) => Ok(b),
+            _ => Err(ConversionError::new("bool", val)),
+        }
+    }
+}
+
+impl TryFromValue for i64 {
+    /// Tries to retrieve `i64` from `Value.`
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// let v: Value = 23i64.into();
+    /// assert_eq!(i64::try_from_value(&v), Ok(23i64));
+    /// let v: Value = u64::MAX.into();
+    /// assert!(i64::try_from_value(&v).is_err());
+    /// ```
+    fn try_from_value(val: &Value) -> Result<i64, ConversionError> {
+        let n = number("i64", val)?;
+        n.as_i64()
+            .ok_or_else(|| ConversionError::out_of_range("i64", n))
+    }
+}
+
+impl TryFromValue for u64 {
+    /// Tries to retrieve `u64` from `Value.`
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// let v: Value = 23u64.into();
+    /// assert_eq!(u64::try_from_value(&v), Ok(23u64));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<u64, ConversionError> {
+        let n = number("u64", val)?;
+        n.as_u64()
+            .ok_or_else(|| ConversionError::out_of_range("u64", n))
+    }
+}
+
+macro_rules! try_from_int {
+    ($($ty:ident)*) => {
+        $(
+            impl TryFromValue for $ty {
+                /// Tries to retrieve the integer from `Value`, failing if it
+                /// is out of range.
+                fn try_from_value(val: &Value) -> Result<$ty, ConversionError> {
+                    let n = number(stringify!($ty), val)?;
+                    n.as_i128()
+                        .and_then(|i| $ty::try_from(i).ok())
+                        .or_else(|| n.as_u128().and_then(|u| $ty::try_from(u).ok()))
+                        .ok_or_else(|| ConversionError::out_of_range(stringify!($ty), n))
+                }
+            }
+        )*
+    };
+}
+
+try_from_int! {
+    i8 i16 i32 i128 isize
+    u8 u16 u32 u128 usize
+}
+
+#[cfg(feature = "decimal")]
+impl TryFromValue for rust_decimal::Decimal {
+    /// Tries to retrieve a `Decimal` from `Value`.
+    ///
+    /// Floats are converted via their shortest decimal representation.
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    /// use rust_decimal::Decimal;
+    ///
+    /// let v: Value = 0.1.into();
+    /// assert_eq!(Decimal::try_from_value(&v), Ok(Decimal::new(1, 1)));
+    /// let v: Value = 3.into();
+    /// assert_eq!(Decimal::try_from_value(&v), Ok(Decimal::from(3)));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<rust_decimal::Decimal, ConversionError> {
+        let n = number("Decimal", val)?;
+        n.to_decimal()
+            .or_else(|| n.as_f64().and_then(crate::number::float_to_decimal))
+            .ok_or_else(|| ConversionError::out_of_range("Decimal", n))
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl TryFromValue for num_bigint::BigInt {
+    fn try_from_value(val: &Value) -> Result<num_bigint::BigInt, ConversionError> {
+        let n = number("BigInt", val)?;
+        n.to_bigint()
+            .ok_or_else(|| ConversionError::out_of_range("BigInt", n))
+    }
+}
+
+impl TryFromValue for f64 {
+    /// Tries to retrieve `f64` from `Value.`
+    ///
+    /// Integers are converted to the nearest `f64`.
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// let v: Value = 23.1f64.into();
+    /// assert_eq!(f64::try_from_value(&v), Ok(23.1f64));
+    /// let v: Value = 23.into();
+    /// assert_eq!(f64::try_from_value(&v), Ok(23f64));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<f64, ConversionError> {
+        number("f64", val).map(Number::to_f64)
+    }
+}
+
+impl TryFromValue for f32 {
+    /// Tries to retrieve `f32` from `Value.`
+    ///
+    /// Numbers are rounded to the nearest `f32`, finite numbers exceeding the
+    /// range of `f32` are an error.
+    fn try_from_value(val: &Value) -> Result<f32, ConversionError> {
+        let n = number("f32", val)?;
+        let f = n.to_f64();
+        if f.is_finite() && f.abs() > f64::from(f32::MAX) {
+            Err(ConversionError::out_of_range("f32", n))
+        } else {
+            Ok(f as f32)
+        }
+    }
+}
+
+impl TryFromValue for char {
+    /// Tries to retrieve a `char` from a `Value::String` holding exactly one
+    /// character.
+    fn try_from_value(val: &Value) -> Result<char, ConversionError> {
+        if let Value::String(ref s) = *val {
+            let mut chars = s.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                return Ok(c);
+            }
+        }
+        Err(ConversionError::new("char", val))
+    }
+}
+
+impl TryFromValue for String {
+    /// Tries to retrieve `String` from `Value.`
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// let v: Value = "foobar".into();
+    /// assert_eq!(String::try_from_value(&v), Ok("foobar".to_owned()));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<String, ConversionError> {
+        match *val {
+            Value::String(ref s) => Ok(s.to_string()),
+            _ => Err(ConversionError::new("string", val)),
+        }
+    }
+}
+
+impl<T> TryFromValue for Vec<T>
+where
+    T: TryFromValue,
+{
+    /// Tries to retrieve `Vec<T>` from `Value.`
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// let v: Value = vec!(1, 2, 3).into();
+    /// assert_eq!(Vec::<i64>::try_from_value(&v), Ok(vec!(1, 2, 3)));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<Vec<T>, ConversionError> {
+        match *val {
+            Value::Array(ref a) => a
+                .iter()
+                .enumerate()
+                .map(|(i, v)| T::try_from_value(v).map_err(|e| e.at_index(i)))
+                .collect(),
+            _ => Err(ConversionError::new("array", val)),
+        }
+    }
+}
+
+#[allow(clippy::implicit_hasher)]
+impl<T> TryFromValue for HashMap<String, T>
+where
+    T: TryFromValue,
+{
+    /// Tries to retrieve `HashMap<String, T>` from `Value.`
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut m = HashMap::new();
+    /// m.insert("a".to_owned(), 1);
+    /// let v: Value = m.clone().into();
+    /// assert_eq!(HashMap::<String, i64>::try_from_value(&v), Ok(m));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<HashMap<String, T>, ConversionError> {
+        match *val {
+            Value::Object(ref o) | Value::Map(ref o) => o
+                .iter()
+                .map(|(k, v)| {
+                    T::try_from_value(v)
+                        .map(|t| (k.clone(), t))
+                        .map_err(|e| e.at_key(k.as_str()))
+                })
+                .collect(),
+            _ => Err(ConversionError::new("map", val)),
+        }
+    }
+}
+
+impl<T> TryFromValue for Option<T>
+where
+    T: TryFromValue,
+{
+    /// Retrieves `None` from `Nil` and `NoValue` and `Some(T)` otherwise.
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// assert_eq!(Option::<u8>::try_from_value(&Value::Nil), Ok(None));
+    /// assert_eq!(Option::<u8>::try_from_value(&1.into()), Ok(Some(1)));
+    /// ```
+    fn try_from_value(val: &Value) -> Result<Option<T>, ConversionError> {
+        match *val {
+            Value::Nil | Value::NoValue => Ok(None),
+            _ => T::try_from_value(val).map(Some),
+        }
+    }
+}
+
+fn array(val: &Value, len: usize) -> Result<&[Value], ConversionError> {
+    match *val {
+        Value::Array(ref a) if a.len() == len => Ok(a),
+        Value::Array(ref a) => Err(ConversionError {
+            expected: "array of matching length",
+            found: format!("array of length {}", a.len()),
+            path: vec![],
+        }),
+        _ => Err(ConversionError::new("array", val)),
+    }
+}
+
+impl<T, const N: usize> TryFromValue for [T; N]
+where
+    T: TryFromValue,
+{
+    /// Tries to retrieve `[T; N]` from an array of length `N`.
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use gtmpl_value::{TryFromValue, Value};
+    ///
+    /// let v: Value = vec![1, 2].into();
+    /// assert_eq!(<[u8; 2]>::try_from_value(&v), Ok([1, 2]));
+    /// assert!(<[u8; 3]>::try_from_value(&v).is_err());
+    /// ```
+    fn try_from_value(val: &Value) -> Result<[T; N], ConversionError> {
+        let v: Vec<T> = Vec::try_from_value(val)?;
+        <[T; N]>::try_from(v).map_err(|_| array(val, N).unwrap_err())
+    }
+}
+
+macro_rules! try_from_tuple {
+    ($($len:literal => ($($name:ident $idx:tt)+))*) => {
+        $(
+            impl<$($name),+> TryFromValue for ($($name,)+)
+            where
+                $($name: TryFromValue,)+
+            {
+                /// Tries to retrieve a tuple from an array of the same length.
+                fn try_from_value(val: &Value) -> Result<Self, ConversionError> {
+                    let a = array(val, $len)?;
+                    Ok(($(
+                        $name::try_from_value(&a[$idx]).map_err(|e| e.at_index($idx))?,
+                    )+))
+                }
+            }
+        )*
+    };
+}
+
+try_from_tuple! {
+    1 => (T0 0)
+    2 => (T0 0 T1 1)
+    3 => (T0 0 T1 1 T2 2)
+    4 => (T0 0 T1 1 T2 2 T3 3)
+    5 => (T0 0 T1 1 T2 2 T3 3 T4 4)
+    6 => (T0 0 T1 1 T2 2 T3 3 T4 4 T5 5)
+    7 => (T0 0 T1 1 T2 2 T3 3 T4 4 T5 5 T6 6)
+    8 => (T0 0 T1 1 T2 2 T3 3 T4 4 T5 5 T6 6 T7 7)
+}
+
+impl<T> TryFromValue for BTreeMap<String, T>
+where
+    T: TryFromValue,
+{
+    /// Tries to retrieve `BTreeMap<String, T>` from `Value.`
+    fn try_from_value(val: &Value) -> Result<BTreeMap<String, T>, ConversionError> {
+        match *val {
+            Value::Object(ref o) | Value::Map(ref o) => o
+                .iter()
+                .map(|(k, v)| {
+                    T::try_from_value(v)
+                        .map(|t| (k.clone(), t))
+                        .map_err(|e| e.at_key(k.as_str()))
+                })
+                .collect(),
+            _ => Err(ConversionError::new("map", val)),
+        }
+    }
+}
+
+#[allow(clippy::implicit_hasher)]
+impl<T> TryFromValue for HashSet<T>
+where
+    T: TryFromValue + Eq + Hash,
+{
+    /// Tries to retrieve `HashSet<T>` from an array.
+    fn try_from_value(val: &Value) -> Result<HashSet<T>, ConversionError> {
+        Vec::try_from_value(val).map(|v| v.into_iter().collect())
+    }
+}
+
+impl<T> TryFromValue for BTreeSet<T>
+where
+    T: TryFromValue + Ord,
+{
+    /// Tries to retrieve `BTreeSet<T>` from an array.
+    fn try_from_value(val: &Value) -> Result<BTreeSet<T>, ConversionError> {
+        Vec::try_from_value(val).map(|v| v.into_iter().collect())
+    }
+}
+
+macro_rules! try_from_pointer {
+    ($($ty:ident)*) => {
+        $(
+            impl<T> TryFromValue for $ty<T>
+            where
+                T: TryFromValue,
+            {
+                fn try_from_value(val: &Value) -> Result<$ty<T>, ConversionError> {
+                    T::try_from_value(val).map($ty::new)
+                }
+            }
+        )*
+    };
+}
+
+try_from_pointer! {
+    Box Rc Arc
+}
+
+/// Fallible, borrowing conversion from `Value`.
+///
+/// Unlike `TryFromValue` this does not clone strings or collections, so
+/// helpers only reading their arguments do not allocate.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{try_from_arg_ref, FuncError, Value};
+///
+/// fn len(args: &[Value]) -> Result<Value, FuncError> {
+///     let s: &str = try_from_arg_ref(args, 0)?;
+///     Ok(s.len().into())
+/// }
+///
+/// assert_eq!(len(&["foo".into()]).unwrap(), Value::from(3));
+/// assert_eq!(
+///     len(&[1.into()]).unwrap_err().to_string(),
+///     "args[0]: expected string, found int"
+/// );
+/// ```
+pub trait TryFromValueRef<'a>: Sized {
+    /// Tries to borrow `Self` from `Value`.
+    fn try_from_value_ref(val: &'a Value) -> Result<Self, ConversionError>;
+}
+
+/// `TryFromValueRef` as a function.
+pub fn try_from_value_ref<'a, T>(val: &'a Value) -> Result<T, ConversionError>
+where
+    T: TryFromValueRef<'a>,
+{
+    T::try_from_value_ref(val)
+}
+
+/// Borrows the `index`th function argument, reporting it as `args[index]`.
+pub fn try_from_arg_ref<'a, T>(args: &'a [Value], index: usize) -> Result<T, ConversionError>
+where
+    T: TryFromValueRef<'a>,
+{
+    let val = args.get(index).unwrap_or(&Value::NoValue);
+    T::try_from_value_ref(val).map_err(|e| e.at_index(index).at_key("args"))
+}
+
+impl<'a> TryFromValueRef<'a> for &'a Value {
+    fn try_from_value_ref(val: &'a Value) -> Result<&'a Value, ConversionError> {
+        Ok(val)
+    }
+}
+
+impl<'a> TryFromValueRef<'a> for &'a str {
+    fn try_from_value_ref(val: &'a Value) -> Result<&'a str, ConversionError> {
+        val.as_str()
+            .ok_or_else(|| ConversionError::new("string", val))
+    }
+}
+
+impl<'a> TryFromValueRef<'a> for &'a Number {
+    fn try_from_value_ref(val: &'a Value) -> Result<&'a Number, ConversionError> {
+        number("number", val)
+    }
+}
+
+impl<'a> TryFromValueRef<'a> for &'a [Value] {
+    fn try_from_value_ref(val: &'a Value) -> Result<&'a [Value], ConversionError> {
+        val.as_array()
+            .ok_or_else(|| ConversionError::new("array", val))
+    }
+}
+
+impl<'a> TryFromValueRef<'a> for &'a Map {
+    fn try_from_value_ref(val: &'a Value) -> Result<&'a Map, ConversionError> {
+        val.as_map().ok_or_else(|| ConversionError::new("map", val))
+    }
+}
+
+impl<'a, T> TryFromValueRef<'a> for Option<T>
+where
+    T: TryFromValueRef<'a>,
+{
+    /// Borrows `None` from `Nil` and `NoValue` and `Some(T)` otherwise.
+    fn try_from_value_ref(val: &'a Value) -> Result<Option<T>, ConversionError> {
+        match *val {
+            Value::Nil | Value::NoValue => Ok(None),
+            _ => T::try_from_value_ref(val).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::FuncError;
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(bool::try_from_value(&true.into()), Ok(true));
+        assert_eq!(
+            bool::try_from_value(&1.into()),
+            Err(ConversionError::new("bool", &1.into()))
+        );
+        assert_eq!(
+            u64::try_from_value(&(-1).into()).unwrap_err().to_string(),
+            "expected u64, found int -1"
+        );
+        assert_eq!(
+            String::try_from_value(&Value::Nil).unwrap_err().to_string(),
+            "expected string, found nil"
+        );
+        assert_eq!(Value::try_from_value(&Value::Nil), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn test_nested_path() {
+        let mut inner = Map::new();
+        inner.insert("tags".to_owned(), vec![1, 2].into());
+        let val = Value::Array(Arc::new(vec![
+            Value::Map(Arc::new(Map::new())),
+            Value::Map(Arc::new(inner)),
+        ]));
+        let err = Vec::<HashMap<String, Vec<bool>>>::try_from_value(&val).unwrap_err();
+        assert_eq!(
+            err.path,
+            vec![
+                PathSegment::Index(1),
+                PathSegment::Key("tags".to_owned()),
+                PathSegment::Index(0)
+            ]
+        );
+        assert_eq!(err.to_string(), "[1].tags[0]: expected bool, found int");
+    }
+
+    #[test]
+    fn test_args() {
+        let args: Vec<Value> = vec!["a".into(), vec!["b"].into()];
+        assert_eq!(try_from_arg::<String>(&args, 0), Ok("a".to_owned()));
+        assert_eq!(
+            try_from_arg::<Vec<String>>(&args, 1),
+            Ok(vec!["b".to_owned()])
+        );
+        let err = try_from_arg::<String>(&args, 2).unwrap_err();
+        assert_eq!(err.to_string(), "args[2]: expected string, found invalid");
+        let err: FuncError = try_from_arg::<i64>(&args, 1).unwrap_err().into();
+        assert_eq!(
+            err.to_string(),
+            "args[1]: expected i64, found []interface {}"
+        );
+    }
+
+    #[test]
+    fn test_narrowing() {
+        assert_eq!(u8::try_from_value(&255.into()), Ok(255));
+        assert_eq!(
+            u8::try_from_value(&256.into()).unwrap_err().to_string(),
+            "expected u8, found int 256"
+        );
+        assert!(u32::try_from_value(&(-1).into()).is_err());
+        assert_eq!(i8::try_from_value(&(-128).into()), Ok(-128));
+        assert!(i8::try_from_value(&(-129).into()).is_err());
+        assert_eq!(
+            i32::try_from_value(&u64::MAX.into()).unwrap_err().expected,
+            "i32"
+        );
+        assert_eq!(usize::try_from_value(&23.into()), Ok(23));
+        assert!(usize::try_from_value(&0.5.into()).is_err());
+        assert_eq!(f32::try_from_value(&0.5.into()), Ok(0.5));
+        assert_eq!(
+            f32::try_from_value(&f64::INFINITY.into()),
+            Ok(f32::INFINITY)
+        );
+        assert_eq!(
+            f32::try_from_value(&f64::MAX.into()).unwrap_err().expected,
+            "f32"
+        );
+        assert!(u64::try_from_value(&1e20.into()).is_err());
+    }
+
+    #[test]
+    fn test_char() {
+        assert_eq!(char::try_from_value(&"ä".into()), Ok('ä'));
+        assert!(char::try_from_value(&"".into()).is_err());
+        assert!(char::try_from_value(&"ab".into()).is_err());
+    }
+
+    #[test]
+    fn test_option() {
+        assert_eq!(Option::<bool>::try_from_value(&Value::NoValue), Ok(None));
+        assert_eq!(Option::<bool>::try_from_value(&true.into()), Ok(Some(true)));
+        assert!(Option::<bool>::try_from_value(&1.into()).is_err());
+    }
+
+    #[test]
+    fn test_tuple() {
+        let val: Value = vec![Value::from(1), "a".into(), Value::Nil].into();
+        assert_eq!(
+            <(u8, String, Option<bool>)>::try_from_value(&val),
+            Ok((1, "a".to_owned(), None))
+        );
+        let err = <(u8, String)>::try_from_value(&val).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected array of matching length, found array of length 3"
+        );
+        let err = <(u8, u8, u8)>::try_from_value(&val).unwrap_err();
+        assert_eq!(err.to_string(), "[1]: expected u8, found string");
+    }
+
+    #[test]
+    fn test_collections() {
+        let val: Value = vec![2, 1, 2].into();
+        let set: BTreeSet<u8> = try_from_value(&val).unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+        let set: HashSet<u8> = try_from_value(&val).unwrap();
+        assert_eq!(set.len(), 2);
+        let mut m = Map::new();
+        m.insert("a".to_owned(), 1.into());
+        let m: BTreeMap<String, Box<u8>> = try_from_value(&Value::Object(Arc::new(m))).unwrap();
+        assert_eq!(m.get("a"), Some(&Box::new(1)));
+        assert_eq!(Rc::<u8>::try_from_value(&1.into()), Ok(Rc::new(1)));
+        assert_eq!(Arc::<u8>::try_from_value(&1.into()), Ok(Arc::new(1)));
+    }
+
+    #[test]
+    fn test_borrowing() {
+        let val: Value = "foo".into();
+        let s: &str = try_from_value_ref(&val).unwrap();
+        assert!(std::ptr::eq(s, val.as_str().unwrap()));
+        let val: Value = vec![1, 2].into();
+        let a: &[Value] = try_from_value_ref(&val).unwrap();
+        assert_eq!(a.len(), 2);
+        assert!(try_from_value_ref::<&Map>(&val).is_err());
+        let n: &Number = try_from_value_ref(&a[0]).unwrap();
+        assert_eq!(n.as_u64(), Some(1));
+        let obj = Value::Object(Arc::new(Map::new()));
+        assert!(try_from_value_ref::<&Map>(&obj).unwrap().is_empty());
+        assert_eq!(try_from_value_ref::<Option<&str>>(&Value::Nil), Ok(None));
+    }
+
+    #[test]
+    fn test_args_ref() {
+        let args: Vec<Value> = vec!["a".into(), Value::Nil];
+        assert_eq!(try_from_arg_ref::<&str>(&args, 0), Ok("a"));
+        assert_eq!(try_from_arg_ref::<Option<&str>>(&args, 1), Ok(None));
+        assert_eq!(
+            try_from_arg_ref::<&str>(&args, 1).unwrap_err().to_string(),
+            "args[1]: expected string, found nil"
+        );
+    }
+}
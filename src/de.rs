@@ -0,0 +1,466 @@
+//! Deserialization of any `serde::Deserialize` type out of a `Value`.
+
+use std::fmt::Display;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, Unexpected, VariantAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use crate::ser::SerdeError;
+use crate::value::{Number, Value};
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Custom(msg.to_string())
+    }
+}
+
+/// Deserializes `T` out of a `Value`.
+///
+/// `Object` and `Map` deserialize as maps or structs, `Array` as sequences or
+/// tuples. `Nil` and `NoValue` deserialize as `None` or `()`. Enums are read
+/// from a string (unit variants) or from a map with the variant name as the
+/// only key, mirroring `to_value`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{de::from_value, Value};
+/// use std::collections::HashMap;
+///
+/// #[derive(serde::Deserialize, Debug, PartialEq)]
+/// struct Args {
+///     a: u8,
+///     b: Option<String>,
+/// }
+///
+/// let mut m = HashMap::new();
+/// m.insert("a".to_owned(), 1);
+/// let val: Value = m.into();
+/// let args: Args = from_value(&val).unwrap();
+/// assert_eq!(args, Args { a: 1, b: None });
+/// ```
+pub fn from_value<'de, T>(val: &'de Value) -> Result<T, SerdeError>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(val)
+}
+
+fn unexpected(val: &Value) -> Unexpected<'_> {
+    match *val {
+        Value::NoValue | Value::Nil => Unexpected::Unit,
+        Value::Bool(b) => Unexpected::Bool(b),
+        Value::String(ref s) => Unexpected::Str(s),
+        Value::Object(_) | Value::Map(_) => Unexpected::Map,
+        Value::Array(_) => Unexpected::Seq,
+        Value::Function(_) => Unexpected::Other("function"),
+        Value::Number(ref n) => {
+            if let Some(n) = n.as_u64() {
+                Unexpected::Unsigned(n)
+            } else if let Some(n) = n.as_i64() {
+                Unexpected::Signed(n)
+            } else if let Some(n) = n.as_f64() {
+                Unexpected::Float(n)
+            } else {
+                Unexpected::Other("number")
+            }
+        }
+    }
+}
+
+/// Returns numbers without a native serde representation, see
+/// `Number::is_native`.
+fn wide_number(val: &Value) -> Option<&Number> {
+    match *val {
+        Value::Number(ref n) if !n.is_native() => Some(n),
+        _ => None,
+    }
+}
+
+/// Deserializes numbers without a native serde representation into integer
+/// types: 128 bit integers natively, everything else is out of range.
+fn visit_wide_int<'de, V>(val: &Value, n: &Number, visitor: V) -> Result<V::Value, SerdeError>
+where
+    V: Visitor<'de>,
+{
+    if let Some(n) = n.as_u128() {
+        visitor.visit_u128(n)
+    } else if let Some(n) = n.as_i128() {
+        visitor.visit_i128(n)
+    } else {
+        Err(de::Error::invalid_type(unexpected(val), &visitor))
+    }
+}
+
+macro_rules! deserialize_int {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+            where
+                V: Visitor<'de>,
+            {
+                match wide_number(self) {
+                    Some(n) => visit_wide_int(self, n, visitor),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &'de Value {
+    type Error = SerdeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match *self {
+            Value::NoValue | Value::Nil => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::String(ref s) => visitor.visit_borrowed_str(s),
+            Value::Object(ref m) | Value::Map(ref m) => {
+                let mut map = MapDeserializer::new(m.iter().map(|(k, v)| (k.as_str(), v)));
+                let res = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(res)
+            }
+            Value::Array(ref a) => {
+                let mut seq = SeqDeserializer::new(a.iter());
+                let res = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(res)
+            }
+            Value::Function(ref f) => Err(SerdeError::Custom(format!(
+                "unable to deserialize function {}",
+                f.name()
+            ))),
+            Value::Number(ref n) => {
+                if let Some(n) = n.as_u64() {
+                    visitor.visit_u64(n)
+                } else if let Some(n) = n.as_i64() {
+                    visitor.visit_i64(n)
+                } else if let Some(n) = n.as_f64() {
+                    visitor.visit_f64(n)
+                } else {
+                    // Like `Number::serialize`, typed targets parse it back.
+                    visitor.visit_str(&n.to_string())
+                }
+            }
+        }
+    }
+
+    deserialize_int! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match wide_number(self) {
+            Some(n) => visitor.visit_f64(n.to_f64()),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match wide_number(self) {
+            Some(n) => visitor.visit_str(&n.to_string()),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match *self {
+            Value::NoValue | Value::Nil => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match *self {
+            Value::String(ref variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: None,
+            }),
+            Value::Map(ref m) if m.len() == 1 => {
+                let (variant, value) = m.iter().next().expect("map has one entry");
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            ref val => Err(de::Error::invalid_type(
+                unexpected(val),
+                &"string or map with a single key",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool char bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for &'de Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    value: Option<&'de Value>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = SerdeError;
+    type Variant = VariantDeserializer<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), SerdeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer<'de> {
+    value: Option<&'de Value>,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer<'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            None | Some(Value::Nil) | Some(Value::NoValue) => Ok(()),
+            Some(val) => Err(de::Error::invalid_type(unexpected(val), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, SerdeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(val) => seed.deserialize(val),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(val @ Value::Array(_)) => de::Deserializer::deserialize_any(val, visitor),
+            Some(val) => Err(de::Error::invalid_type(unexpected(val), &"tuple variant")),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(val @ Value::Object(_)) | Some(val @ Value::Map(_)) => {
+                de::Deserializer::deserialize_any(val, visitor)
+            }
+            Some(val) => Err(de::Error::invalid_type(unexpected(val), &"struct variant")),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ser::to_value;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    enum E {
+        A,
+        B(u8),
+        C(u8, u8),
+        D { x: bool },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Foo {
+        name: String,
+        tags: Vec<String>,
+        ratio: f64,
+        opt: Option<i8>,
+        e: Vec<E>,
+        unit: (),
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let foo = Foo {
+            name: "foo".to_owned(),
+            tags: vec!["a".to_owned(), "b".to_owned()],
+            ratio: 2.0,
+            opt: Some(-1),
+            e: vec![E::A, E::B(1), E::C(1, 2), E::D { x: true }],
+            unit: (),
+        };
+        let val = to_value(&foo).unwrap();
+        assert_eq!(from_value::<Foo>(&val), Ok(foo));
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(from_value::<u8>(&23.into()), Ok(23));
+        assert_eq!(from_value::<f32>(&23.into()), Ok(23.0));
+        assert_eq!(from_value::<i64>(&(-23).into()), Ok(-23));
+        assert_eq!(from_value::<char>(&"x".into()), Ok('x'));
+        assert_eq!(from_value::<Option<u8>>(&Value::NoValue), Ok(None));
+        assert_eq!(from_value::<()>(&Value::Nil), Ok(()));
+        assert_eq!(
+            from_value::<(u8, String)>(&vec![Value::from(1), "a".into()].into()),
+            Ok((1, "a".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let val: Value = "foo".into();
+        let s: &str = from_value(&val).unwrap();
+        assert_eq!(s, "foo");
+    }
+
+    #[test]
+    fn test_map() {
+        let mut m = HashMap::new();
+        m.insert("a".to_owned(), vec![1, 2]);
+        let val: Value = m.clone().into();
+        assert_eq!(from_value::<HashMap<String, Vec<u8>>>(&val), Ok(m));
+    }
+
+    #[test]
+    fn test_wide_numbers() {
+        let max: Value = u128::MAX.into();
+        assert_eq!(from_value::<u128>(&max), Ok(u128::MAX));
+        assert_eq!(from_value::<String>(&max), Ok(u128::MAX.to_string()));
+        assert_eq!(from_value::<f64>(&max), Ok(u128::MAX as f64));
+        assert!(from_value::<u64>(&max).is_err());
+        assert_eq!(from_value::<Value>(&max), Ok(u128::MAX.to_string().into()));
+        let min: Value = i128::MIN.into();
+        assert_eq!(from_value::<i128>(&min), Ok(i128::MIN));
+        assert!(from_value::<u128>(&min).is_err());
+        let back: Number = from_value(&min).unwrap();
+        assert_eq!(back.as_i128(), Some(i128::MIN));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal() {
+        let val: Value = rust_decimal::Decimal::new(150, 2).into();
+        assert_eq!(from_value::<f64>(&val), Ok(1.5));
+        assert_eq!(from_value::<f32>(&val), Ok(1.5));
+        assert_eq!(from_value::<String>(&val), Ok("1.50".to_owned()));
+        assert!(from_value::<i64>(&val).is_err());
+        assert_eq!(from_value::<Value>(&val), Ok("1.50".into()));
+        let back: Number = from_value(&val).unwrap();
+        assert_eq!(back.to_string(), "1.50");
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_bigint() {
+        let big = Number::from(u128::MAX).checked_mul(&4.into()).unwrap();
+        let val = Value::Number(big.clone());
+        assert_eq!(from_value::<String>(&val), Ok(big.to_string()));
+        assert_eq!(from_value::<Value>(&val), Ok(big.to_string().into()));
+        let back: Number = from_value(&val).unwrap();
+        assert_eq!(back, big);
+        assert!(from_value::<u128>(&val).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            from_value::<u8>(&300.into()).unwrap_err().to_string(),
+            "invalid value: integer `300`, expected u8"
+        );
+        assert_eq!(
+            from_value::<String>(&true.into()).unwrap_err().to_string(),
+            "invalid type: boolean `true`, expected a string"
+        );
+        assert_eq!(
+            from_value::<Foo>(&Value::Map(Default::default()))
+                .unwrap_err()
+                .to_string(),
+            "missing field `name`"
+        );
+        assert!(from_value::<(u8,)>(&vec![1, 2].into()).is_err());
+        assert!(from_value::<E>(&1.into()).is_err());
+    }
+}
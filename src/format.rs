@@ -0,0 +1,200 @@
+//! Formatting of `Value`s following Go's `fmt` package.
+//!
+//! `Value`'s `Display` implementation prints values the way `fmt.Sprint`
+//! prints the corresponding Go values, so templates ported from Go render
+//! identically.
+
+use std::fmt::{self, Write};
+
+use crate::value::Value;
+
+/// Writes `f` like Go's `strconv.FormatFloat(f, 'g', -1, 64)`, which is what
+/// `%v` prints for floats.
+///
+/// The shortest digits that round trip are printed in exponent form (`1e+06`,
+/// `1e-05`) if the exponent is less than -4 or at least 6. `NaN` and
+/// infinities print as `NaN`, `+Inf` and `-Inf`.
+pub(crate) fn write_float<W: Write>(w: &mut W, f: f64) -> fmt::Result {
+    if f.is_nan() {
+        return w.write_str("NaN");
+    }
+    if f.is_infinite() {
+        return w.write_str(if f > 0.0 { "+Inf" } else { "-Inf" });
+    }
+    if f.is_sign_negative() {
+        w.write_char('-')?;
+    }
+    if f == 0.0 {
+        return w.write_char('0');
+    }
+    // Rust's `{:e}` yields the same shortest round-tripping digits as Go.
+    let sci = format!("{:e}", f.abs());
+    let (mantissa, exp) = sci.split_at(sci.find('e').unwrap_or(sci.len()));
+    let exp: i32 = exp[1..].parse().map_err(|_| fmt::Error)?;
+    let digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    if !(-4..6).contains(&exp) {
+        let sign = if exp < 0 { '-' } else { '+' };
+        return write!(w, "{}e{}{:02}", mantissa, sign, exp.abs());
+    }
+    if exp < 0 {
+        w.write_str("0.")?;
+        for _ in 0..-exp - 1 {
+            w.write_char('0')?;
+        }
+        return w.write_str(&digits);
+    }
+    let int_len = exp as usize + 1;
+    if digits.len() <= int_len {
+        write!(w, "{:0<width$}", digits, width = int_len)
+    } else {
+        write!(w, "{}.{}", &digits[..int_len], &digits[int_len..])
+    }
+}
+
+/// Writes `val` like Go's `%v` verb.
+///
+/// * `Nil` prints as `<nil>` and `NoValue` as `<no value>`.
+/// * Arrays print as `[a b c]`.
+/// * Maps print as `map[a:1 b:2]` with sorted keys.
+/// * Objects print like Go structs as `{1 2}`. Go uses the declaration order
+///   of the fields; here they follow the key order of the `Map`, so this only
+///   approximates Go's output.
+pub(crate) fn write_value<W: Write>(w: &mut W, val: &Value) -> fmt::Result {
+    match *val {
+        Value::NoValue => w.write_str("<no value>"),
+        Value::Nil => w.write_str("<nil>"),
+        Value::Bool(b) => write!(w, "{}", b),
+        Value::String(ref s) => w.write_str(s),
+        Value::Function(ref func) => write!(w, "{}", func),
+        Value::Number(ref n) => write!(w, "{}", n),
+        Value::Array(ref a) => {
+            w.write_char('[')?;
+            write_separated(w, a.iter(), write_value)?;
+            w.write_char(']')
+        }
+        Value::Object(ref o) => {
+            w.write_char('{')?;
+            write_separated(w, o.iter(), |w, (_, v)| write_value(w, v))?;
+            w.write_char('}')
+        }
+        Value::Map(ref m) => {
+            w.write_str("map[")?;
+            write_separated(w, m.iter(), |w, (k, v)| {
+                write!(w, "{}:", k)?;
+                write_value(w, v)
+            })?;
+            w.write_char(']')
+        }
+    }
+}
+
+fn write_separated<W, I, T, F>(w: &mut W, items: I, mut write_item: F) -> fmt::Result
+where
+    W: Write,
+    I: Iterator<Item = T>,
+    F: FnMut(&mut W, T) -> fmt::Result,
+{
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            w.write_char(' ')?;
+        }
+        write_item(w, item)?;
+    }
+    Ok(())
+}
+
+/// Formats `vals` like Go's `fmt.Sprint`.
+///
+/// Spaces are added between operands when neither is a string.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{sprint, Value};
+///
+/// let vals: Vec<Value> = vec![1.into(), 2.into(), "a".into(), 3.into()];
+/// assert_eq!(sprint(&vals), "1 2a3");
+/// ```
+pub fn sprint(vals: &[Value]) -> String {
+    let mut s = String::new();
+    let mut prev_string = true;
+    for (i, val) in vals.iter().enumerate() {
+        let is_string = matches!(*val, Value::String(_));
+        if i > 0 && !is_string && !prev_string {
+            s.push(' ');
+        }
+        // Writing to a `String` does not fail.
+        let _ = write_value(&mut s, val);
+        prev_string = is_string;
+    }
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::map;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(Value::NoValue.to_string(), "<no value>");
+        assert_eq!(Value::Nil.to_string(), "<nil>");
+        assert_eq!(Value::from(true).to_string(), "true");
+        assert_eq!(Value::from("foo bar").to_string(), "foo bar");
+        assert_eq!(Value::from(-23).to_string(), "-23");
+        assert_eq!(Value::from(2.5).to_string(), "2.5");
+    }
+
+    #[test]
+    fn test_array() {
+        let val: Value = vec![1, 2, 3].into();
+        assert_eq!(val.to_string(), "[1 2 3]");
+        let val: Value = vec!["a b", "c"].into();
+        assert_eq!(val.to_string(), "[a b c]");
+        let val = Value::Array(Arc::new(vec![]));
+        assert_eq!(val.to_string(), "[]");
+        let val = Value::Array(Arc::new(vec![1.into(), Value::Nil, vec![2, 3].into()]));
+        assert_eq!(val.to_string(), "[1 <nil> [2 3]]");
+    }
+
+    #[test]
+    fn test_map() {
+        let val = Value::Map(Arc::new(map(&[
+            ("b", 2.into()),
+            ("a", 1.into()),
+            ("c", "x".into()),
+        ])));
+        assert_eq!(val.to_string(), "map[a:1 b:2 c:x]");
+        let val = Value::Map(Arc::new(map(&[])));
+        assert_eq!(val.to_string(), "map[]");
+        let val = Value::Map(Arc::new(map(&[
+            ("z", vec![1, 2].into()),
+            ("m", Value::Map(Arc::new(map(&[("k", Value::Nil)])))),
+        ])));
+        assert_eq!(val.to_string(), "map[m:map[k:<nil>] z:[1 2]]");
+    }
+
+    #[test]
+    fn test_object() {
+        let val = Value::Object(Arc::new(map(&[("Name", "foo".into()), ("Age", 23.into())])));
+        assert_eq!(val.to_string(), "{23 foo}");
+        let val = Value::Array(Arc::new(vec![Value::Object(Arc::new(map(&[(
+            "A",
+            true.into(),
+        )])))]));
+        assert_eq!(val.to_string(), "[{true}]");
+    }
+
+    #[test]
+    fn test_sprint() {
+        assert_eq!(sprint(&[]), "");
+        assert_eq!(sprint(&["a".into(), "b".into()]), "ab");
+        assert_eq!(sprint(&[1.into(), 2.into()]), "1 2");
+        assert_eq!(
+            sprint(&["a".into(), 1.into(), 2.into(), "b".into()]),
+            "a1 2b"
+        );
+        assert_eq!(sprint(&[Value::Nil, true.into()]), "<nil> true");
+    }
+}
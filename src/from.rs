@@ -1,6 +1,11 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
+
+use crate::convert::TryFromValue;
 use crate::value::{Func, Function, Value};
 
 macro_rules! from_num {
@@ -16,8 +21,8 @@ macro_rules! from_num {
 }
 
 from_num! {
-    i8 i16 i32 i64 isize
-    u8 u16 u32 u64 usize
+    i8 i16 i32 i64 i128 isize
+    u8 u16 u32 u64 u128 usize
     f32 f64
 }
 
@@ -37,6 +42,65 @@ impl From<bool> for Value {
     }
 }
 
+#[cfg(feature = "bigint")]
+impl From<num_bigint::BigInt> for Value {
+    /// Convert a big integer to `Value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    /// use num_bigint::BigInt;
+    ///
+    /// let x: Value = (BigInt::from(u128::MAX) * 2u8).into();
+    /// assert_eq!(x.to_string(), "680564733841876926926749214863536422910");
+    /// ```
+    fn from(n: num_bigint::BigInt) -> Self {
+        Value::Number(n.into())
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<rust_decimal::Decimal> for Value {
+    /// Convert a decimal to `Value`, keeping its scale.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let x: Value = Decimal::new(150, 2).into();
+    /// assert_eq!(x.to_string(), "1.50");
+    /// ```
+    fn from(n: rust_decimal::Decimal) -> Self {
+        Value::Number(n.into())
+    }
+}
+
+impl From<()> for Value {
+    /// Convert `()` to `Value::Nil`
+    fn from(_: ()) -> Self {
+        Value::Nil
+    }
+}
+
+impl From<char> for Value {
+    /// Convert char to `Value`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let x: Value = 'a'.into();
+    /// assert_eq!(x, Value::from("a"));
+    /// ```
+    fn from(c: char) -> Self {
+        Value::String(c.to_string().into())
+    }
+}
+
 impl<'a> From<&'a String> for Value {
     /// Convert &String to `Value`
     ///
@@ -49,7 +113,7 @@ impl<'a> From<&'a String> for Value {
     /// let x: Value = s.into();
     /// ```
     fn from(s: &'a String) -> Self {
-        Value::String(s.clone())
+        Value::String(s.as_str().into())
     }
 }
 
@@ -65,11 +129,11 @@ impl From<String> for Value {
     /// let x: Value = s.into();
     /// ```
     fn from(s: String) -> Self {
-        Value::String(s)
+        Value::String(s.into())
     }
 }
 
-impl<'a> From<&'a str> for Value {
+impl From<&str> for Value {
     /// Convert &str to `Value`
     ///
     /// # Examples
@@ -81,7 +145,7 @@ impl<'a> From<&'a str> for Value {
     /// let x: Value = s.into();
     /// ```
     fn from(f: &str) -> Self {
-        Value::String(f.to_string())
+        Value::String(f.into())
     }
 }
 
@@ -98,7 +162,7 @@ impl<'a> From<Cow<'a, str>> for Value {
     /// let x: Value = s.into();
     /// ```
     fn from(f: Cow<'a, str>) -> Self {
-        Value::String(f.to_string())
+        Value::String(f.into())
     }
 }
 
@@ -116,13 +180,29 @@ impl From<Func> for Value {
     /// let x: Value = (f as Func).into();
     /// ```
     fn from(f: Func) -> Self {
-        Value::Function(Function { f })
+        Value::Function(f.into())
+    }
+}
+
+impl From<Function> for Value {
+    /// Convert Function to `Value`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::{Function, Value};
+    ///
+    /// let f = Function::new("first", |a: &[Value]| Ok(a[0].clone()));
+    /// let x: Value = f.into();
+    /// ```
+    fn from(f: Function) -> Self {
+        Value::Function(f)
     }
 }
 
 impl<T> From<Vec<T>> for Value
 where
-    T: Into<Value> + Clone,
+    T: Into<Value>,
 {
     /// Convert Vec to `Value`
     ///
@@ -135,7 +215,7 @@ where
     /// let x: Value = v.into();
     /// ```
     fn from(f: Vec<T>) -> Self {
-        Value::Array(f.iter().cloned().map(|x| x.into()).collect())
+        Value::Array(Arc::new(f.into_iter().map(|x| x.into()).collect()))
     }
 }
 
@@ -154,13 +234,13 @@ where
     /// let x: Value = v.into();
     /// ```
     fn from(f: &'a [T]) -> Self {
-        Value::Array(f.iter().cloned().map(|x| x.into()).collect())
+        Value::Array(Arc::new(f.iter().cloned().map(|x| x.into()).collect()))
     }
 }
 
 impl<T> From<HashMap<String, T>> for Value
 where
-    T: Into<Value> + Clone,
+    T: Into<Value>,
 {
     /// Convert HashMap<String, T> to `Value`
     ///
@@ -175,193 +255,232 @@ where
     /// let x: Value = m.into();
     /// ```
     fn from(f: HashMap<String, T>) -> Self {
-        Value::Map(
-            f.iter()
-                .map(|(s, x)| (s.clone(), x.clone().into()))
-                .collect(),
-        )
+        Value::Map(Arc::new(
+            f.into_iter().map(|(s, x)| (s, x.into())).collect(),
+        ))
     }
 }
 
-impl<T> From<Option<T>> for Value
+impl<T> From<BTreeMap<String, T>> for Value
 where
-    T: Into<Value> + Clone,
+    T: Into<Value>,
 {
-    /// Convert Option<T> to `Value`
+    /// Convert BTreeMap<String, T> to `Value`
     ///
     /// # Examples
     ///
     /// ```rust
     /// use gtmpl_value::Value;
+    /// use std::collections::BTreeMap;
     ///
-    /// let i = Some(1);
-    /// let x: Value = i.into();
+    /// let mut m = BTreeMap::new();
+    /// m.insert("hello".to_owned(), 123);
+    /// let x: Value = m.into();
     /// ```
-    fn from(f: Option<T>) -> Self {
-        match f {
-            Some(x) => x.into(),
-            _ => Value::NoValue,
-        }
+    fn from(f: BTreeMap<String, T>) -> Self {
+        Value::Map(Arc::new(
+            f.into_iter().map(|(s, x)| (s, x.into())).collect(),
+        ))
     }
 }
 
-/// Convert Value into something.
-pub trait FromValue<T> {
-    /// Tries to retrieve `T` from `Value.`
-    fn from_value(val: &Value) -> Option<T>;
-}
-
-impl FromValue<i64> for i64 {
-    /// Tries to retrieve `i64` from `Value.`
+impl<'a, T> From<&'a HashMap<String, T>> for Value
+where
+    T: Into<Value> + Clone,
+{
+    /// Convert &HashMap<String, T> to `Value`
     ///
-    /// # Examples:
+    /// # Examples
     ///
     /// ```rust
-    /// use gtmpl_value::{FromValue, Value};
+    /// use gtmpl_value::Value;
+    /// use std::collections::HashMap;
     ///
-    /// let v: Value = 23i64.into();
-    /// let i = i64::from_value(&v);
-    /// assert_eq!(i, Some(23i64));
+    /// let mut m = HashMap::new();
+    /// m.insert("hello".to_owned(), 123);
+    /// let x: Value = (&m).into();
     /// ```
-    fn from_value(val: &Value) -> Option<i64> {
-        if let Value::Number(ref n) = *val {
-            n.as_i64()
-        } else {
-            None
-        }
+    fn from(f: &'a HashMap<String, T>) -> Self {
+        Value::Map(Arc::new(
+            f.iter()
+                .map(|(s, x)| (s.clone(), x.clone().into()))
+                .collect(),
+        ))
     }
 }
 
-impl FromValue<u64> for u64 {
-    /// Tries to retrieve `u64` from `Value.`
-    ///
-    /// # Examples:
-    ///
-    /// ```rust
-    /// use gtmpl_value::{FromValue, Value};
+#[cfg(feature = "indexmap")]
+impl<T, S> From<IndexMap<String, T, S>> for Value
+where
+    T: Into<Value>,
+{
+    /// Convert IndexMap<String, T> to `Value`
     ///
-    /// let v: Value = 23u64.into();
-    /// let i = u64::from_value(&v);
-    /// assert_eq!(i, Some(23u64));
-    /// ```
-    fn from_value(val: &Value) -> Option<u64> {
-        if let Value::Number(ref n) = *val {
-            n.as_u64()
-        } else {
-            None
-        }
+    /// The entries of the resulting `Value::Map` are ordered by key.
+    fn from(f: IndexMap<String, T, S>) -> Self {
+        Value::Map(Arc::new(
+            f.into_iter().map(|(s, x)| (s, x.into())).collect(),
+        ))
     }
 }
 
-impl FromValue<f64> for f64 {
-    /// Tries to retrieve `f64` from `Value.`
-    ///
-    /// # Examples:
-    ///
-    /// ```rust
-    /// use gtmpl_value::{FromValue, Value};
+impl<T> From<VecDeque<T>> for Value
+where
+    T: Into<Value>,
+{
+    /// Convert VecDeque to `Value`
+    fn from(f: VecDeque<T>) -> Self {
+        Value::Array(Arc::new(f.into_iter().map(|x| x.into()).collect()))
+    }
+}
+
+impl<T> From<HashSet<T>> for Value
+where
+    T: Into<Value>,
+{
+    /// Convert HashSet to `Value`
     ///
-    /// let v: Value = 23.1f64.into();
-    /// let i = f64::from_value(&v);
-    /// assert_eq!(i, Some(23.1f64));
-    /// ```
-    fn from_value(val: &Value) -> Option<f64> {
-        if let Value::Number(ref n) = *val {
-            n.as_f64()
-        } else {
-            None
-        }
+    /// The order of the elements is unspecified.
+    fn from(f: HashSet<T>) -> Self {
+        Value::Array(Arc::new(f.into_iter().map(|x| x.into()).collect()))
     }
 }
 
-impl FromValue<String> for String {
-    /// Tries to retrieve `String` from `Value.`
+impl<T> From<BTreeSet<T>> for Value
+where
+    T: Into<Value>,
+{
+    /// Convert BTreeSet to `Value`
     ///
-    /// # Examples:
+    /// # Examples
     ///
     /// ```rust
-    /// use gtmpl_value::{FromValue, Value};
+    /// use gtmpl_value::Value;
+    /// use std::collections::BTreeSet;
     ///
-    /// let v: Value = "foobar".into();
-    /// let s = String::from_value(&v);
-    /// assert_eq!(s, Some("foobar".to_owned()));
+    /// let s: BTreeSet<_> = vec![3, 1, 2].into_iter().collect();
+    /// let x: Value = s.into();
+    /// assert_eq!(x.to_string(), "[1 2 3]");
     /// ```
-    fn from_value(val: &Value) -> Option<String> {
-        if let Value::String(ref s) = *val {
-            Some(s.clone())
-        } else {
-            None
-        }
+    fn from(f: BTreeSet<T>) -> Self {
+        Value::Array(Arc::new(f.into_iter().map(|x| x.into()).collect()))
     }
 }
 
-impl<T> FromValue<Vec<T>> for Vec<T>
+impl<T, const N: usize> From<[T; N]> for Value
 where
-    T: FromValue<T>,
+    T: Into<Value>,
 {
-    /// Tries to retrieve `Vec<T>` from `Value.`
+    /// Convert array to `Value`
     ///
-    /// # Examples:
+    /// # Examples
     ///
     /// ```rust
-    /// use gtmpl_value::{FromValue, Value};
+    /// use gtmpl_value::Value;
     ///
-    /// let v: Value = vec!(1, 2, 3).into();
-    /// let v: Option<Vec<i64>> = Vec::from_value(&v);
-    /// assert_eq!(v, Some(vec!(1, 2, 3)));
+    /// let x: Value = [1, 2, 3].into();
     /// ```
-    fn from_value(val: &Value) -> Option<Vec<T>> {
-        if let Value::Array(ref a) = *val {
-            let v: Vec<T> = a.iter().flat_map(|v| T::from_value(v)).collect();
-            if v.len() == a.len() {
-                return Some(v);
+    fn from(f: [T; N]) -> Self {
+        Value::Array(Arc::new(
+            IntoIterator::into_iter(f).map(|x| x.into()).collect(),
+        ))
+    }
+}
+
+macro_rules! from_tuple {
+    ($(($($name:ident $idx:tt)+))*) => {
+        $(
+            impl<$($name),+> From<($($name,)+)> for Value
+            where
+                $($name: Into<Value>,)+
+            {
+                /// Convert tuple to `Value::Array`
+                fn from(f: ($($name,)+)) -> Self {
+                    Value::Array(Arc::new(vec![$(f.$idx.into()),+]))
+                }
             }
-        }
-        None
+        )*
+    };
+}
+
+from_tuple! {
+    (T0 0)
+    (T0 0 T1 1)
+    (T0 0 T1 1 T2 2)
+    (T0 0 T1 1 T2 2 T3 3)
+    (T0 0 T1 1 T2 2 T3 3 T4 4)
+    (T0 0 T1 1 T2 2 T3 3 T4 4 T5 5)
+    (T0 0 T1 1 T2 2 T3 3 T4 4 T5 5 T6 6)
+    (T0 0 T1 1 T2 2 T3 3 T4 4 T5 5 T6 6 T7 7)
+}
+
+impl<T> From<Box<T>> for Value
+where
+    T: Into<Value>,
+{
+    /// Convert Box<T> to `Value`
+    fn from(f: Box<T>) -> Self {
+        (*f).into()
     }
 }
 
-#[allow(clippy::implicit_hasher)]
-impl<T> FromValue<HashMap<String, T>> for HashMap<String, T>
+impl<T> From<Option<T>> for Value
 where
-    T: FromValue<T>,
+    T: Into<Value>,
 {
-    /// Tries to retrieve `HashMap<String, T>` from `Value.`
+    /// Convert Option<T> to `Value`
     ///
-    /// # Examples:
+    /// # Examples
     ///
     /// ```rust
-    /// use gtmpl_value::{FromValue, Value};
-    /// use std::collections::HashMap;
+    /// use gtmpl_value::Value;
     ///
-    /// let mut m = HashMap::new();
-    /// m.insert("a".to_owned(), 1);
-    /// let v: Value = m.into();
-    /// let m: Option<HashMap<String, i64>> = HashMap::from_value(&v);
-    /// assert!(m.is_some());
-    /// if let Some(m) = m {
-    ///   assert_eq!(m.get("a"), Some(&1));
-    /// }
+    /// let i = Some(1);
+    /// let x: Value = i.into();
     /// ```
-    fn from_value(val: &Value) -> Option<HashMap<String, T>> {
-        match *val {
-            Value::Object(ref o) | Value::Map(ref o) => {
-                let m: HashMap<String, T> = o
-                    .iter()
-                    .map(|(s, v)| (s.clone(), T::from_value(v)))
-                    .flat_map(|(s, t)| t.map(|t| (s, t)))
-                    .collect();
-                if m.len() == o.len() {
-                    Some(m)
-                } else {
-                    None
-                }
-            }
-            _ => None,
+    fn from(f: Option<T>) -> Self {
+        match f {
+            Some(x) => x.into(),
+            _ => Value::NoValue,
         }
     }
 }
 
+/// Convert Value into something.
+///
+/// Implemented for every type implementing `TryFromValue`. Use
+/// `TryFromValue` to find out why a conversion failed.
+///
+/// # Examples:
+///
+/// ```rust
+/// use gtmpl_value::{FromValue, Value};
+/// use std::collections::HashMap;
+///
+/// let v: Value = vec!(1, 2, 3).into();
+/// let v: Option<Vec<i64>> = Vec::from_value(&v);
+/// assert_eq!(v, Some(vec!(1, 2, 3)));
+///
+/// let mut m = HashMap::new();
+/// m.insert("a".to_owned(), 1);
+/// let v: Value = m.into();
+/// let m: Option<HashMap<String, i64>> = HashMap::from_value(&v);
+/// assert_eq!(m.and_then(|m| m.get("a").cloned()), Some(1));
+/// ```
+pub trait FromValue<T> {
+    /// Tries to retrieve `T` from `Value.`
+    fn from_value(val: &Value) -> Option<T>;
+}
+
+impl<T> FromValue<T> for T
+where
+    T: TryFromValue,
+{
+    fn from_value(val: &Value) -> Option<T> {
+        T::try_from_value(val).ok()
+    }
+}
+
 /// `FromValue` wrapped in a macro (required for `gtmpl_fn!` macro).
 ///
 /// # Examples:
@@ -430,4 +549,68 @@ mod test {
             panic!();
         }
     }
+
+    #[test]
+    fn test_map_order() {
+        let m: HashMap<String, i32> = (0..100).map(|i| (format!("k{:03}", 99 - i), i)).collect();
+        let val: Value = m.into();
+        if let Value::Map(obj) = val {
+            let keys: Vec<_> = obj.keys().cloned().collect();
+            let expected: Vec<_> = (0..100).map(|i| format!("k{:03}", i)).collect();
+            assert_eq!(keys, expected);
+        } else {
+            panic!();
+        }
+    }
+
+    #[test]
+    fn test_tuple_and_unit() {
+        let val: Value = (1, "a", ()).into();
+        assert_eq!(val.to_string(), "[1 a <nil>]");
+        let val: Value = Box::new('x').into();
+        assert_eq!(val, Value::from("x"));
+    }
+
+    #[test]
+    fn test_collections() {
+        let d: VecDeque<_> = vec![1, 2].into_iter().collect();
+        assert_eq!(Value::from(d), Value::from(vec![1, 2]));
+        let s: HashSet<_> = vec![1].into_iter().collect();
+        assert_eq!(Value::from(s), Value::from(vec![1]));
+        let mut m = HashMap::new();
+        m.insert("a".to_owned(), 1);
+        assert_eq!(Value::from(&m), Value::from(m));
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn test_indexmap() {
+        let mut m = IndexMap::new();
+        m.insert("b".to_owned(), 2);
+        m.insert("a".to_owned(), 1);
+        assert_eq!(Value::from(m).to_string(), "map[a:1 b:2]");
+    }
+
+    #[test]
+    fn test_128() {
+        assert_eq!(Value::from(u64::MAX as u128), Value::from(u64::MAX));
+        assert_eq!(Value::from(i64::MIN as i128), Value::from(i64::MIN));
+        assert_eq!(Value::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Value::from(i128::MIN).to_string(), i128::MIN.to_string());
+    }
+
+    #[test]
+    fn test_move_without_clone() {
+        struct NoClone(u8);
+        impl From<NoClone> for Value {
+            fn from(n: NoClone) -> Self {
+                n.0.into()
+            }
+        }
+        assert_eq!(Value::from(vec![NoClone(1)]), Value::from(vec![1]));
+        let mut m = HashMap::new();
+        m.insert("a".to_owned(), NoClone(1));
+        assert_eq!(Value::from(m).to_string(), "map[a:1]");
+        assert_eq!(Value::from(Some(NoClone(1))), Value::from(1));
+    }
 }
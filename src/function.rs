@@ -0,0 +1,292 @@
+use std::cmp::PartialEq;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::value::{FuncError, Value};
+
+/// Function type supported by `gtmpl_value`.
+pub type Func = fn(&[Value]) -> Result<Value, FuncError>;
+
+/// Closure type supported by `gtmpl_value`.
+///
+/// Unlike `Func` a closure may capture state (e.g. a database handle or
+/// configuration).
+pub type DynFunc = dyn Fn(&[Value]) -> Result<Value, FuncError> + Send + Sync;
+
+/// Number of arguments a `Function` accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity {
+    /// Exactly `n` arguments.
+    Exactly(usize),
+    /// At least `n` arguments.
+    AtLeast(usize),
+    /// Any number of arguments.
+    Variadic,
+}
+
+impl Arity {
+    /// Checks whether `n` arguments satisfy this arity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Arity;
+    ///
+    /// assert!(Arity::Exactly(2).accepts(2));
+    /// assert!(!Arity::AtLeast(2).accepts(1));
+    /// assert!(Arity::Variadic.accepts(0));
+    /// ```
+    pub fn accepts(self, n: usize) -> bool {
+        match self {
+            Arity::Exactly(x) => n == x,
+            Arity::AtLeast(x) => n >= x,
+            Arity::Variadic => true,
+        }
+    }
+
+    fn check(self, name: &str, n: usize) -> Result<(), FuncError> {
+        match self {
+            _ if self.accepts(n) => Ok(()),
+            Arity::Exactly(x) => Err(FuncError::ExactlyXArgs(name.to_owned(), x)),
+            Arity::AtLeast(x) => Err(FuncError::AtLeastXArgs(name.to_owned(), x)),
+            Arity::Variadic => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Arity::Exactly(n) => write!(f, "{}", n),
+            Arity::AtLeast(n) => write!(f, "{}+", n),
+            Arity::Variadic => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Callable {
+    Func(Func),
+    Closure(Arc<DynFunc>),
+}
+
+/// Wrapper struct for `Func` and closures.
+///
+/// Besides the callable itself a `Function` carries a name, an `Arity` and
+/// optional descriptions of its parameter and return types. The arity is
+/// checked by `call` before the wrapped function is invoked.
+///
+/// Two `Function`s are equal if they wrap the same `Func` or the same shared
+/// closure.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{Arity, FuncError, Function, Value};
+///
+/// let greeting = String::from("Hello");
+/// let f = Function::new("greet", move |args: &[Value]| {
+///     Ok(Value::from(format!("{} {}", greeting, args[0])))
+/// })
+/// .with_arity(Arity::Exactly(1))
+/// .with_params(["string"])
+/// .with_returns("string");
+///
+/// assert_eq!(f.call(&["world".into()]).unwrap(), Value::from("Hello world"));
+/// assert_eq!(f.to_string(), "greet(string) string");
+/// match f.call(&[]) {
+///     Err(FuncError::ExactlyXArgs(name, 1)) => assert_eq!(name, "greet"),
+///     _ => panic!(),
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Function {
+    /// The wrapped function, called without checking the `Arity`.
+    #[deprecated(note = "use `Function::call` instead")]
+    pub f: Arc<DynFunc>,
+    name: String,
+    arity: Arity,
+    params: Vec<String>,
+    returns: Option<String>,
+    callable: Callable,
+}
+
+impl Function {
+    /// Creates a named, variadic `Function` from a closure.
+    pub fn new<N, F>(name: N, f: F) -> Function
+    where
+        N: Into<String>,
+        F: Fn(&[Value]) -> Result<Value, FuncError> + Send + Sync + 'static,
+    {
+        Function::with_callable(name.into(), Callable::Closure(Arc::new(f)))
+    }
+
+    #[allow(deprecated)]
+    fn with_callable(name: String, f: Callable) -> Function {
+        Function {
+            f: match f {
+                Callable::Func(f) => Arc::new(f),
+                Callable::Closure(ref f) => Arc::clone(f),
+            },
+            name,
+            arity: Arity::Variadic,
+            params: vec![],
+            returns: None,
+            callable: f,
+        }
+    }
+
+    /// Sets the name of the `Function`.
+    pub fn with_name<N: Into<String>>(mut self, name: N) -> Function {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the `Arity` checked before each call.
+    pub fn with_arity(mut self, arity: Arity) -> Function {
+        self.arity = arity;
+        self
+    }
+
+    /// Sets descriptions of the parameter types.
+    pub fn with_params<I, S>(mut self, params: I) -> Function
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.params = params.into_iter().map(|p| p.as_ref().to_owned()).collect();
+        self
+    }
+
+    /// Sets a description of the return type.
+    pub fn with_returns<S: Into<String>>(mut self, returns: S) -> Function {
+        self.returns = Some(returns.into());
+        self
+    }
+
+    /// Returns the name of the `Function`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the `Arity` of the `Function`.
+    pub fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    /// Returns the descriptions of the parameter types.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Returns the description of the return type.
+    pub fn returns(&self) -> Option<&str> {
+        self.returns.as_deref()
+    }
+
+    /// Calls the wrapped function with `args`.
+    ///
+    /// Returns `FuncError::ExactlyXArgs` or `FuncError::AtLeastXArgs` without
+    /// calling the wrapped function if `args` does not match the `Arity`.
+    pub fn call(&self, args: &[Value]) -> Result<Value, FuncError> {
+        self.arity.check(&self.name, args.len())?;
+        match self.callable {
+            Callable::Func(f) => f(args),
+            Callable::Closure(ref f) => f(args),
+        }
+    }
+}
+
+impl From<Func> for Function {
+    fn from(f: Func) -> Self {
+        Function::with_callable(String::from("anonymous"), Callable::Func(f))
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Function) -> bool {
+        match (&self.callable, &other.callable) {
+            (Callable::Func(s), Callable::Func(o)) => *s as usize == *o as usize,
+            (Callable::Closure(s), Callable::Closure(o)) => Arc::ptr_eq(s, o),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("params", &self.params)
+            .field("returns", &self.returns)
+            .finish()
+    }
+}
+
+/// Prints the name of the `Function` followed by its parameter and return
+/// types if they are known.
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.params.is_empty() {
+            write!(f, "({})", self.params.join(", "))?;
+        }
+        if let Some(ref returns) = self.returns {
+            write!(f, " {}", returns)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arity_check() {
+        fn f(a: &[Value]) -> Result<Value, FuncError> {
+            Ok(a.len().into())
+        }
+        let func = Function::from(f as Func)
+            .with_name("len")
+            .with_arity(Arity::AtLeast(2));
+        match func.call(&[Value::Nil]) {
+            Err(FuncError::AtLeastXArgs(name, 2)) => assert_eq!(name, "len"),
+            r => panic!("unexpected {:?}", r),
+        }
+        assert_eq!(
+            func.call(&[Value::Nil, Value::Nil]).unwrap(),
+            Value::from(2)
+        );
+        let err = func.with_arity(Arity::Exactly(1)).call(&[]).unwrap_err();
+        assert_eq!(err.to_string(), "len requires exactly 1 argument(s)");
+    }
+
+    #[test]
+    fn test_debug() {
+        let func = Function::new("add", |_: &[Value]| Ok(Value::Nil))
+            .with_arity(Arity::Exactly(2))
+            .with_params(["int", "int"])
+            .with_returns("int");
+        assert_eq!(
+            format!("{:?}", func),
+            r#"Function { name: "add", arity: Exactly(2), params: ["int", "int"], returns: Some("int") }"#
+        );
+        assert_eq!(func.to_string(), "add(int, int) int");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_field() {
+        fn f(a: &[Value]) -> Result<Value, FuncError> {
+            Ok(a.len().into())
+        }
+        let func = Function::from(f as Func).with_arity(Arity::Exactly(0));
+        assert_eq!((func.f)(&[Value::Nil]).unwrap(), Value::from(1));
+        assert_eq!(func, Function::from(f as Func));
+        let n = 2;
+        let func = Function::new("add", move |a: &[Value]| Ok((a.len() + n).into()));
+        assert_eq!((func.f)(&[]).unwrap(), Value::from(2));
+    }
+}
@@ -0,0 +1,442 @@
+//! Indexing into and updating `Value`s in place.
+//!
+//! Maps and arrays are copied on write if they are shared with another
+//! `Value`, see `Value::as_map_mut`.
+
+use std::collections::btree_map::Entry;
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+use crate::path::PathError;
+use crate::value::{Map, Value, NO_VALUE};
+
+/// Types that can index into a `Value`: `usize` for arrays and `str` or
+/// `String` for maps and objects.
+///
+/// This trait is sealed and can not be implemented outside of this crate.
+pub trait ValueIndex: private::Sealed {
+    /// Borrows the element at this index of `val` if there is one.
+    fn index_into<'v>(&self, val: &'v Value) -> Option<&'v Value>;
+
+    /// Mutably borrows the element at this index of `val` if there is one.
+    fn index_into_mut<'v>(&self, val: &'v mut Value) -> Option<&'v mut Value>;
+
+    /// Removes the element at this index of `val` if there is one. Fails for
+    /// objects, which never lose fields.
+    fn remove_from(&self, val: &mut Value) -> Result<Option<Value>, PathError>;
+
+    /// Mutably borrows the element at this index of `val` for `IndexMut`,
+    /// inserting it into maps if missing. Panics if that is not possible.
+    fn index_or_insert<'v>(&self, val: &'v mut Value) -> &'v mut Value;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: ?Sized + Sealed> Sealed for &T {}
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, val: &'v Value) -> Option<&'v Value> {
+        val.as_array().and_then(|a| a.get(*self))
+    }
+
+    fn index_into_mut<'v>(&self, val: &'v mut Value) -> Option<&'v mut Value> {
+        match *val {
+            Value::Array(ref a) if *self >= a.len() => None,
+            _ => val.as_array_mut().and_then(|a| a.get_mut(*self)),
+        }
+    }
+
+    fn remove_from(&self, val: &mut Value) -> Result<Option<Value>, PathError> {
+        Ok(match *val {
+            Value::Array(ref a) if *self >= a.len() => None,
+            _ => val.as_array_mut().map(|a| a.remove(*self)),
+        })
+    }
+
+    fn index_or_insert<'v>(&self, val: &'v mut Value) -> &'v mut Value {
+        match *val {
+            Value::Array(ref mut a) => {
+                let len = a.len();
+                Arc::make_mut(a).get_mut(*self).unwrap_or_else(|| {
+                    panic!("index {} out of range for array of length {}", self, len)
+                })
+            }
+            _ => panic!("cannot index {} with {}", val.type_name(), self),
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, val: &'v Value) -> Option<&'v Value> {
+        val.as_map().and_then(|m| m.get(self))
+    }
+
+    fn index_into_mut<'v>(&self, val: &'v mut Value) -> Option<&'v mut Value> {
+        match *val {
+            Value::Map(ref m) | Value::Object(ref m) if !m.contains_key(self) => None,
+            _ => val.as_map_mut().and_then(|m| m.get_mut(self)),
+        }
+    }
+
+    fn remove_from(&self, val: &mut Value) -> Result<Option<Value>, PathError> {
+        match *val {
+            Value::Object(_) => Err(PathError::CantEvaluateField(
+                self.to_owned(),
+                val.type_name(),
+            )),
+            Value::Map(ref m) if !m.contains_key(self) => Ok(None),
+            _ => Ok(val.as_map_mut().and_then(|m| m.remove(self))),
+        }
+    }
+
+    fn index_or_insert<'v>(&self, val: &'v mut Value) -> &'v mut Value {
+        if let Value::NoValue | Value::Nil = *val {
+            *val = Value::Map(Arc::default());
+        }
+        match *val {
+            Value::Map(ref mut m) => Arc::make_mut(m)
+                .entry(self.to_owned())
+                .or_insert(Value::NoValue),
+            Value::Object(ref mut o) => Arc::make_mut(o)
+                .get_mut(self)
+                .unwrap_or_else(|| panic!("can't evaluate field {} in type struct", self)),
+            _ => panic!("cannot index {} with {:?}", val.type_name(), self),
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, val: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(val)
+    }
+
+    fn index_into_mut<'v>(&self, val: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(val)
+    }
+
+    fn remove_from(&self, val: &mut Value) -> Result<Option<Value>, PathError> {
+        self.as_str().remove_from(val)
+    }
+
+    fn index_or_insert<'v>(&self, val: &'v mut Value) -> &'v mut Value {
+        self.as_str().index_or_insert(val)
+    }
+}
+
+impl<T: ?Sized + ValueIndex> ValueIndex for &T {
+    fn index_into<'v>(&self, val: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(val)
+    }
+
+    fn index_into_mut<'v>(&self, val: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(val)
+    }
+
+    fn remove_from(&self, val: &mut Value) -> Result<Option<Value>, PathError> {
+        (**self).remove_from(val)
+    }
+
+    fn index_or_insert<'v>(&self, val: &'v mut Value) -> &'v mut Value {
+        (**self).index_or_insert(val)
+    }
+}
+
+impl Value {
+    /// Borrows an element of an array or an entry of a map or an object.
+    ///
+    /// Returns `None` if the index is missing or the `Value` can not be
+    /// indexed that way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let v: Value = vec![1, 2].into();
+    /// assert_eq!(v.get(1), Some(&Value::from(2)));
+    /// assert_eq!(v.get(2), None);
+    /// assert_eq!(v.get("foo"), None);
+    /// ```
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Mutably borrows an element of an array or an entry of a map or an
+    /// object.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use gtmpl_value::Value;
+    ///
+    /// let mut m = HashMap::new();
+    /// m.insert("foo".to_owned(), 1);
+    /// let mut v: Value = m.into();
+    /// *v.get_mut("foo").unwrap() = 2.into();
+    /// assert_eq!(v.to_string(), "map[foo:2]");
+    /// ```
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+
+    /// Inserts an entry into a map or sets a field of an object and returns
+    /// the previous value.
+    ///
+    /// Like Go structs, objects have a fixed set of fields: setting a missing
+    /// field fails, as does inserting into a `Value` that is neither a map
+    /// nor an object.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::{Map, Value};
+    ///
+    /// let mut v = Value::from(Map::new());
+    /// assert_eq!(v.insert("foo", 1), Ok(None));
+    /// assert_eq!(v.insert("foo", 2), Ok(Some(1.into())));
+    /// assert_eq!(
+    ///     Value::Nil.insert("foo", 1).unwrap_err().to_string(),
+    ///     "can't evaluate field foo in type nil"
+    /// );
+    /// ```
+    pub fn insert<K: Into<String>, V: Into<Value>>(
+        &mut self,
+        key: K,
+        val: V,
+    ) -> Result<Option<Value>, PathError> {
+        let key = key.into();
+        Ok(self.map_mut(&key)?.insert(key, val.into()))
+    }
+
+    /// Removes an element of an array or an entry of a map and returns it.
+    ///
+    /// Array elements after the removed one are shifted down. Fails for
+    /// objects, which like Go structs never lose fields.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let mut v: Value = vec![1, 2, 3].into();
+    /// assert_eq!(v.remove(0), Ok(Some(1.into())));
+    /// assert_eq!(v.remove(5), Ok(None));
+    /// assert_eq!(v.to_string(), "[2 3]");
+    /// ```
+    pub fn remove<I: ValueIndex>(&mut self, index: I) -> Result<Option<Value>, PathError> {
+        index.remove_from(self)
+    }
+
+    /// Appends an element to an array.
+    ///
+    /// Fails if the `Value` is not an array.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let mut v: Value = vec![1].into();
+    /// v.push(2).unwrap();
+    /// assert_eq!(v.to_string(), "[1 2]");
+    /// ```
+    pub fn push<V: Into<Value>>(&mut self, val: V) -> Result<(), PathError> {
+        let type_name = self.type_name();
+        let a = self
+            .as_array_mut()
+            .ok_or(PathError::CantAppend(type_name))?;
+        a.push(val.into());
+        Ok(())
+    }
+
+    /// Gets the entry of `key` in a map or an object for in-place
+    /// manipulation.
+    ///
+    /// Fails like `insert` for missing object fields and for `Value`s that
+    /// are neither maps nor objects.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::{Map, Value};
+    ///
+    /// let mut v = Value::from(Map::new());
+    /// v.entry("foo").unwrap().or_insert_with(|| vec![1].into());
+    /// v.entry("foo").unwrap().and_modify(|e| e.push(2).unwrap());
+    /// assert_eq!(v.to_string(), "map[foo:[1 2]]");
+    /// ```
+    pub fn entry<K: Into<String>>(
+        &mut self,
+        key: K,
+    ) -> Result<Entry<'_, String, Value>, PathError> {
+        let key = key.into();
+        Ok(self.map_mut(&key)?.entry(key))
+    }
+
+    /// Borrows the map `key` can be inserted into: any map, or an object
+    /// that already has the field.
+    fn map_mut(&mut self, key: &str) -> Result<&mut Map, PathError> {
+        let type_name = self.type_name();
+        match *self {
+            Value::Map(ref mut m) => Ok(Arc::make_mut(m)),
+            Value::Object(ref mut o) if o.contains_key(key) => Ok(Arc::make_mut(o)),
+            _ => Err(PathError::CantEvaluateField(key.to_owned(), type_name)),
+        }
+    }
+}
+
+/// Indexes into arrays, maps and objects like `Value::get`, yielding
+/// `NoValue` if the index is missing.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use gtmpl_value::Value;
+///
+/// let mut m = HashMap::new();
+/// m.insert("foo".to_owned(), vec![1, 2]);
+/// let v: Value = m.into();
+/// assert_eq!(v["foo"][1], Value::from(2));
+/// assert_eq!(v["bar"][0], Value::NoValue);
+/// ```
+impl<I: ValueIndex> Index<I> for Value {
+    type Output = Value;
+
+    fn index(&self, index: I) -> &Value {
+        index.index_into(self).unwrap_or(&NO_VALUE)
+    }
+}
+
+/// Mutably indexes into arrays, maps and objects.
+///
+/// A missing map key is inserted as `NoValue` and `NoValue` or `Nil` is
+/// turned into an empty `Map` first when indexed by a key.
+///
+/// # Panics
+///
+/// Panics if an array index is out of range, an object field is missing or
+/// the `Value` can not be indexed that way.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::Value;
+///
+/// let mut v = Value::Nil;
+/// v["foo"]["bar"] = 1.into();
+/// v["baz"] = vec![1, 2].into();
+/// v["baz"][0] = 3.into();
+/// assert_eq!(v.to_string(), "map[baz:[3 2] foo:map[bar:1]]");
+/// ```
+impl<I: ValueIndex> IndexMut<I> for Value {
+    fn index_mut(&mut self, index: I) -> &mut Value {
+        index.index_or_insert(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let mut m = Map::new();
+        m.insert("a".to_owned(), vec![1, 2].into());
+        let v = Value::from(m);
+        assert_eq!(v.get("a").and_then(|a| a.get(1)), Some(&Value::from(2)));
+        let key = "a".to_owned();
+        assert_eq!(v.get(&key), v.get("a"));
+        assert_eq!(v.get("b"), None);
+        assert_eq!(v.get(0), None);
+        assert_eq!(Value::from(1).get("a"), None);
+    }
+
+    #[test]
+    fn test_copy_on_write() {
+        let a: Value = vec![1, 2].into();
+        let mut b = a.clone();
+        *b.get_mut(0).unwrap() = 3.into();
+        assert_eq!(b.remove(1), Ok(Some(2.into())));
+        b.push(4).unwrap();
+        assert_eq!(a.to_string(), "[1 2]");
+        assert_eq!(b.to_string(), "[3 4]");
+    }
+
+    #[test]
+    fn test_index() {
+        let mut v = Value::from(Map::new());
+        v["a"] = vec![1, 2].into();
+        v["b"]["c"] = "d".into();
+        assert_eq!(v["a"][1], Value::from(2));
+        assert_eq!(v["a"][2], Value::NoValue);
+        assert_eq!(v["b"]["c"], Value::from("d"));
+        assert_eq!(v["b"]["c"]["e"], Value::NoValue);
+        assert_eq!(v[0], Value::NoValue);
+        let key = "a".to_owned();
+        v[&key][0] = 3.into();
+        assert_eq!(v.to_string(), "map[a:[3 2] b:map[c:d]]");
+    }
+
+    #[test]
+    #[should_panic(expected = "index 2 out of range for array of length 2")]
+    fn test_index_mut_out_of_range() {
+        let mut v: Value = vec![1, 2].into();
+        v[2] = 3.into();
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index string with \"a\"")]
+    fn test_index_mut_type() {
+        let mut v: Value = "foo".into();
+        v["a"] = 3.into();
+    }
+
+    #[test]
+    fn test_object_fields() {
+        let mut fields = Map::new();
+        fields.insert("Name".to_owned(), "foo".into());
+        let mut v = Value::Object(Arc::new(fields));
+        assert_eq!(v.insert("Name", "bar"), Ok(Some("foo".into())));
+        v.entry("Name").unwrap().and_modify(|e| *e = "baz".into());
+        assert_eq!(v["Name"], Value::from("baz"));
+        let missing = PathError::CantEvaluateField("Age".to_owned(), "struct");
+        assert_eq!(v.insert("Age", 1), Err(missing.clone()));
+        assert_eq!(v.entry("Age").err(), Some(missing.clone()));
+        assert_eq!(v.ensure_path(&["Age"]).err(), Some(missing.clone()));
+        assert_eq!(v.remove("Age"), Err(missing));
+        assert_eq!(
+            v.remove("Name"),
+            Err(PathError::CantEvaluateField("Name".to_owned(), "struct"))
+        );
+        assert_eq!(v.to_string(), "{baz}");
+    }
+
+    #[test]
+    #[should_panic(expected = "can't evaluate field Age in type struct")]
+    fn test_index_mut_object_field() {
+        let mut v = Value::Object(Arc::new(Map::new()));
+        v["Age"] = 1.into();
+    }
+
+    #[test]
+    fn test_type_errors() {
+        let mut v: Value = vec![1].into();
+        assert_eq!(
+            v.insert("a", 1).unwrap_err().to_string(),
+            "can't evaluate field a in type []interface {}"
+        );
+        assert!(v.entry("a").is_err());
+        assert_eq!(
+            Value::from("a").push(1),
+            Err(PathError::CantAppend("string"))
+        );
+        assert_eq!(v.get_mut("a"), None);
+        assert_eq!(v.remove("a"), Ok(None));
+    }
+}
@@ -7,15 +7,33 @@
 //! The [`From`](https://doc.rust-lang.org/std/convert/trait.From.html) trait is
 //! implemented for:
 //!
-//! * `String, &str`
-//! * most numeric types `u64, u32, …, i64, i32, …, f64, f32`
-//! * `bool`
-//! * `Vec<Value>, &[Value]`
-//! * `HashMap<String, Value>`
+//! * `String, &str, char`
+//! * numeric types `u128, u64, …, i128, i64, …, f64, f32` (and `BigInt` and
+//!   `Decimal` with the `bigint` and `decimal` features)
+//! * `bool, ()`
+//! * `Vec<Value>, &[Value], [Value; N], VecDeque<Value>`, sets and tuples
+//! * `HashMap<String, Value>`, `BTreeMap<String, Value>` and `IndexMap<String,
+//!   Value>` (with the `indexmap` feature)
+//! * `Option<T>` and `Box<T>`
+//! * `Func` and `Function` (which can wrap closures)
 //!
 //! [`gtmpl_derive`](https://github.com/fiji-flo/gtmpl_derive) provides a custom
 //! `derive` for structs.
 //!
+//! With the `serde` feature `Value` implements `Serialize` and `Deserialize`
+//! using an untagged representation. Numbers without a native serde type
+//! (beyond 64 bits, big integers and decimals) are written as strings and read
+//! back as strings by `Value`, but as numbers by typed targets such as `u128`
+//! or `Number`. `TaggedValue` provides a lossless, tagged representation
+//! instead. `to_value` and `de::from_value` convert between `Value` and any
+//! type implementing `Serialize` or `Deserialize`.
+//!
+//! # Thread safety
+//!
+//! `Value`, `Function` and `FuncError` are `Send + Sync`, so a context can be
+//! shared across threads, e.g. behind an `Arc`. Closures wrapped by
+//! `Function::new` must be `Send + Sync` too. This is checked at compile time.
+//!
 //! # Examples
 //!
 //! ```rust
@@ -28,24 +46,98 @@
 //! }
 //! ```
 
+pub mod compare;
+mod convert;
+#[cfg(feature = "serde")]
+pub mod de;
+mod format;
 mod from;
+mod function;
+mod index;
+mod literal;
 mod number;
+mod path;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "serde")]
+mod tagged;
+mod truth;
 mod value;
 
+pub use crate::compare::CompareError;
+pub use crate::convert::*;
+pub use crate::format::sprint;
 pub use crate::from::*;
+pub use crate::index::ValueIndex;
+pub use crate::literal::LiteralError;
+pub use crate::path::{PathError, ValuePath};
+#[cfg(feature = "serde")]
+pub use crate::ser::{to_value, SerdeError};
+#[cfg(feature = "serde")]
+pub use crate::tagged::{TaggedRef, TaggedValue};
+pub use crate::truth::Truth;
 pub use crate::value::*;
 
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Value>();
+    assert_send_sync::<Function>();
+    assert_send_sync::<FuncError>();
+    assert_send_sync::<Map>();
+    assert_send_sync::<Number>();
+};
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_share_across_threads() {
+        let ctx = Arc::new(Value::from(vec![1, 2, 3]));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let ctx = Arc::clone(&ctx);
+                std::thread::spawn(move || ctx.to_string())
+            })
+            .collect();
+        for h in handles {
+            assert_eq!(h.join().unwrap(), "[1 2 3]");
+        }
+    }
 
     #[test]
     fn test_function_cmp() {
         fn f(a: &[Value]) -> Result<Value, FuncError> {
             Ok(a[0].clone())
         }
-        let f1 = Function { f };
-        let f2 = Function { f };
+        let f1 = Function::from(f as Func);
+        let f2 = Function::from(f as Func);
         assert_eq!(f1, f2);
     }
+
+    #[test]
+    fn test_closure_cmp() {
+        let offset = 23;
+        let f1 = Function::new("add", move |a: &[Value]| Ok((offset + a.len()).into()));
+        let f2 = f1.clone().with_name("renamed");
+        let f3 = Function::new("add", move |a: &[Value]| Ok((offset + a.len()).into()));
+        assert_eq!(f1, f2);
+        assert_ne!(f1, f3);
+        assert_eq!(f2.name(), "renamed");
+        assert_eq!(f1.call(&[Value::Nil]).unwrap(), Value::from(24));
+    }
+
+    #[test]
+    fn test_clone_shares_data() {
+        let a: Value = vec!["foo"].into();
+        let mut b = a.clone();
+        match (&a, &b) {
+            (Value::Array(x), Value::Array(y)) => assert!(Arc::ptr_eq(x, y)),
+            _ => panic!(),
+        }
+        b.as_array_mut().unwrap()[0] = "bar".into();
+        assert_eq!(a, vec!["foo"].into());
+        assert_eq!(b, vec!["bar"].into());
+    }
 }
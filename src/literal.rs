@@ -0,0 +1,574 @@
+//! Parsing of Go constants like `0x1F`, `1_000`, `0x1p-2`, `'a'` and
+//! `"foo\n"` into `Number`s and `Value`s.
+//!
+//! The rules follow Go's `text/template/parse`: integers become `int`s,
+//! literals containing `.`, `e` or `p` (except hexadecimal integers) become
+//! `float64`s and character constants become the `int` value of the
+//! character. Integer constants are limited to 64 bits like in Go.
+//!
+//! Unlike Go, string constants must be valid UTF-8 once unescaped, since
+//! `Value` strings are `str`s: `"\xff"` is a `LiteralError::InvalidUtf8`.
+
+use std::convert::TryFrom;
+use thiserror::Error;
+
+use crate::value::{Number, Value};
+
+/// Errors returned when parsing a literal.
+///
+/// The messages match the ones of Go's `text/template/parse`.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum LiteralError {
+    #[error("malformed character constant: {0}")]
+    MalformedCharacter(String),
+    #[error("illegal number syntax: {0:?}")]
+    IllegalNumber(String),
+    #[error("integer overflow: {0:?}")]
+    IntegerOverflow(String),
+    #[error("invalid syntax: {0}")]
+    InvalidSyntax(String),
+    #[error("string constant is not valid UTF-8: {0}")]
+    InvalidUtf8(String),
+}
+
+/// Port of Go's `strconv.underscoreOK`: underscores may only separate digits
+/// or follow a base prefix.
+fn underscore_ok(s: &str) -> bool {
+    let s = s.strip_prefix(|c| c == '+' || c == '-').unwrap_or(s);
+    let b = s.as_bytes();
+    let mut saw = b'^';
+    let mut i = 0;
+    let mut hex = false;
+    if b.len() >= 2 && b[0] == b'0' && matches!(b[1].to_ascii_lowercase(), b'b' | b'o' | b'x') {
+        i = 2;
+        saw = b'0';
+        hex = b[1].eq_ignore_ascii_case(&b'x');
+    }
+    for &c in &b[i..] {
+        if c.is_ascii_digit() || hex && c.is_ascii_hexdigit() {
+            saw = b'0';
+        } else if c == b'_' {
+            if saw != b'0' {
+                return false;
+            }
+            saw = b'_';
+        } else if saw == b'_' {
+            return false;
+        } else {
+            saw = b'!';
+        }
+    }
+    saw != b'_'
+}
+
+/// Splits off an optional sign, returning whether it was negative.
+fn split_sign(s: &str) -> (bool, &str) {
+    match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    }
+}
+
+/// Splits off a base prefix like Go's `strconv.ParseUint` with base 0.
+fn split_base(s: &str) -> (u32, &str) {
+    let b = s.as_bytes();
+    if b.len() >= 2 && b[0] == b'0' {
+        match b[1].to_ascii_lowercase() {
+            b'x' => return (16, &s[2..]),
+            b'o' => return (8, &s[2..]),
+            b'b' => return (2, &s[2..]),
+            _ => return (8, &s[1..]),
+        }
+    }
+    (10, s)
+}
+
+/// Parses an integer, `None` if it is malformed or out of range.
+fn parse_int(s: &str) -> Option<Number> {
+    let (neg, s) = split_sign(s);
+    let (base, digits) = split_base(s);
+    if digits.is_empty() {
+        return None;
+    }
+    let mut n: u64 = 0;
+    for c in digits.chars() {
+        let d = c.to_digit(base)?;
+        n = n.checked_mul(u64::from(base))?.checked_add(u64::from(d))?;
+    }
+    if !neg {
+        Some(n.into())
+    } else if n <= 1 << 63 {
+        Some(i64::try_from(-i128::from(n)).ok()?.into())
+    } else {
+        None
+    }
+}
+
+fn is_digits(s: &str, radix: u32) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_digit(radix))
+}
+
+/// Multiplies `x` by `2^e` without overflowing intermediate results. Exact
+/// if the result is representable.
+fn scale2(mut x: f64, mut e: i64) -> f64 {
+    let step = 2f64.powi(1000);
+    while e > 1000 && x.is_finite() {
+        x *= step;
+        e -= 1000;
+    }
+    while e < -1000 && x != 0.0 {
+        x /= step;
+        e += 1000;
+    }
+    x * 2f64.powi(e as i32)
+}
+
+/// Parses the mantissa and binary exponent of a hexadecimal float.
+fn parse_hex_float(mantissa: &str, exp: &str) -> Option<f64> {
+    let (int, frac) = match mantissa.find('.') {
+        Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+        None => (mantissa, ""),
+    };
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    let (exp_neg, exp_digits) = split_sign(exp);
+    if !is_digits(exp_digits, 10) {
+        return None;
+    }
+    let exp = exp_digits.parse::<i64>().unwrap_or(i64::MAX).min(1 << 20);
+    let mut e = if exp_neg { -exp } else { exp };
+    let mut m: u128 = 0;
+    let mut sticky = false;
+    for (i, c) in int.chars().chain(frac.chars()).enumerate() {
+        let d = c.to_digit(16)?;
+        let in_frac = i >= int.len();
+        if m >> 120 == 0 {
+            m = m * 16 + u128::from(d);
+            if in_frac {
+                e -= 4;
+            }
+        } else {
+            sticky |= d != 0;
+            if !in_frac {
+                e += 4;
+            }
+        }
+    }
+    if sticky {
+        m |= 1;
+    }
+    Some(round_to_f64(m, e))
+}
+
+/// Rounds `m * 2^e` to the nearest `f64`, ties to even.
+///
+/// `m` is cut to the precision of the result (53 bits, fewer for subnormals)
+/// before scaling, so it is rounded only once. A sticky low bit of `m` stands
+/// for truncated non-zero digits.
+fn round_to_f64(mut m: u128, mut e: i64) -> f64 {
+    if m == 0 {
+        return 0.0;
+    }
+    let len = i64::from(128 - m.leading_zeros());
+    // The lowest bit of a subnormal is worth 2^-1074.
+    let keep = (len + e + 1074).min(53);
+    let shift = len - keep;
+    if shift > len {
+        return 0.0;
+    }
+    if shift > 0 {
+        let shift = shift as u32;
+        let dropped = m & (u128::MAX >> (128 - shift));
+        let half = 1u128 << (shift - 1);
+        m = m.checked_shr(shift).unwrap_or(0);
+        e += i64::from(shift);
+        if dropped > half || dropped == half && m & 1 == 1 {
+            m += 1;
+        }
+    }
+    scale2(m as f64, e)
+}
+
+/// Parses a float following Go's syntax, `None` if it is malformed or out of
+/// range.
+fn parse_float(s: &str) -> Option<f64> {
+    let (neg, body) = split_sign(s);
+    let hex = body
+        .as_bytes()
+        .get(..2)
+        .is_some_and(|p| p.eq_ignore_ascii_case(b"0x"));
+    let f = if hex {
+        let body = &body[2..];
+        let p = body.find(['p', 'P'])?;
+        parse_hex_float(&body[..p], &body[p + 1..])?
+    } else {
+        let (mantissa, exp) = match body.find(['e', 'E']) {
+            Some(i) => (&body[..i], Some(&body[i + 1..])),
+            None => (body, None),
+        };
+        let (int, frac) = match mantissa.find('.') {
+            Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+            None => (mantissa, ""),
+        };
+        let digits_ok = |d: &str| d.is_empty() || is_digits(d, 10);
+        if int.is_empty() && frac.is_empty() || !digits_ok(int) || !digits_ok(frac) {
+            return None;
+        }
+        if let Some(exp) = exp {
+            if !is_digits(split_sign(exp).1, 10) {
+                return None;
+            }
+        }
+        body.parse::<f64>().ok()?
+    };
+    if f.is_infinite() {
+        return None;
+    }
+    Some(if neg { -f } else { f })
+}
+
+/// Reads one possibly escaped character of a quoted literal, like Go's
+/// `strconv.UnquoteChar`. Escapes yielding a single byte (`\x` and octal)
+/// are returned as `Err(byte)`.
+fn unquote_char(s: &mut std::str::Chars<'_>, quote: char) -> Option<Result<char, u8>> {
+    let c = s.next()?;
+    if c == quote || c == '\n' {
+        return None;
+    }
+    if c != '\\' {
+        return Some(Ok(c));
+    }
+    let c = s.next()?;
+    let simple = match c {
+        'a' => Some('\x07'),
+        'b' => Some('\x08'),
+        'f' => Some('\x0c'),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        'v' => Some('\x0b'),
+        '\\' => Some('\\'),
+        '\'' | '"' if c == quote => Some(c),
+        _ => None,
+    };
+    if let Some(c) = simple {
+        return Some(Ok(c));
+    }
+    let mut take = |n: usize, radix: u32| -> Option<u32> {
+        let digits: String = s.by_ref().take(n).collect();
+        if digits.chars().count() != n || !is_digits(&digits, radix) {
+            return None;
+        }
+        u32::from_str_radix(&digits, radix).ok()
+    };
+    match c {
+        'x' => take(2, 16).map(|b| Err(b as u8)),
+        'u' => take(4, 16).and_then(char::from_u32).map(Ok),
+        'U' => take(8, 16).and_then(char::from_u32).map(Ok),
+        '0'..='7' => {
+            let rest = take(2, 8)?;
+            let b = c.to_digit(8)? * 64 + rest;
+            u8::try_from(b).ok().map(Err)
+        }
+        _ => None,
+    }
+}
+
+/// Parses a character constant like `'a'` or `'\n'`.
+fn parse_char(s: &str) -> Option<u32> {
+    let inner = s.strip_prefix('\'')?.strip_suffix('\'')?;
+    let mut chars = inner.chars();
+    let c = match unquote_char(&mut chars, '\'')? {
+        Ok(c) => u32::from(c),
+        Err(b) => u32::from(b),
+    };
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(c)
+}
+
+/// Parses an interpreted (`"…"`) or raw (`` `…` ``) string literal.
+fn parse_string(s: &str) -> Option<Vec<u8>> {
+    if let Some(raw) = s.strip_prefix('`') {
+        let raw = raw.strip_suffix('`')?;
+        if raw.contains('`') {
+            return None;
+        }
+        return Some(raw.bytes().filter(|&b| b != b'\r').collect());
+    }
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while !chars.as_str().is_empty() {
+        match unquote_char(&mut chars, '"')? {
+            Ok(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Err(b) => bytes.push(b),
+        }
+    }
+    Some(bytes)
+}
+
+impl Number {
+    /// Parses a Go numeric or character constant.
+    ///
+    /// Integers may use `0x`, `0o`, `0b` or legacy `0` octal prefixes and
+    /// `_` separators and must fit into 64 bits. Literals containing `.`,
+    /// `e` or `p` (except hexadecimal integers like `0x1e`) are floats, even
+    /// if they are integral. Character constants are their code point.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// assert_eq!(Number::parse_go_literal("0x1F"), Ok(31.into()));
+    /// assert_eq!(Number::parse_go_literal("-0b101"), Ok((-5).into()));
+    /// assert_eq!(Number::parse_go_literal("1_000"), Ok(1000.into()));
+    /// assert_eq!(Number::parse_go_literal("0x1p-2"), Ok(0.25.into()));
+    /// assert_eq!(Number::parse_go_literal("1e3").unwrap().as_f64(), Some(1000.0));
+    /// assert_eq!(Number::parse_go_literal("'a'"), Ok(97.into()));
+    /// assert_eq!(
+    ///     Number::parse_go_literal("1__0").unwrap_err().to_string(),
+    ///     "illegal number syntax: \"1__0\""
+    /// );
+    /// ```
+    pub fn parse_go_literal(text: &str) -> Result<Number, LiteralError> {
+        if text.starts_with('\'') {
+            return parse_char(text)
+                .map(Number::from)
+                .ok_or_else(|| LiteralError::MalformedCharacter(text.to_owned()));
+        }
+        if text.contains('_') && !underscore_ok(text) {
+            return Err(LiteralError::IllegalNumber(text.to_owned()));
+        }
+        let stripped = text.replace('_', "");
+        if let Some(n) = parse_int(&stripped) {
+            return Ok(n);
+        }
+        match parse_float(&stripped) {
+            Some(_) if !text.contains(|c| ".eEpP".contains(c)) => {
+                Err(LiteralError::IntegerOverflow(text.to_owned()))
+            }
+            Some(f) => Ok(Number::float(f)),
+            None => Err(LiteralError::IllegalNumber(text.to_owned())),
+        }
+    }
+}
+
+impl Value {
+    /// Parses a Go constant: `true`, `false`, `nil`, a number or character
+    /// constant (see `Number::parse_go_literal`) or an interpreted or raw
+    /// string literal.
+    ///
+    /// Strings that are not valid UTF-8 once unescaped, like `"\xff"`, are
+    /// valid in Go but can not be represented and fail with
+    /// `LiteralError::InvalidUtf8`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// assert_eq!(Value::parse_literal("nil"), Ok(Value::Nil));
+    /// assert_eq!(Value::parse_literal("true"), Ok(true.into()));
+    /// assert_eq!(Value::parse_literal(r#""a\tb""#), Ok("a\tb".into()));
+    /// assert_eq!(Value::parse_literal(r"`a\tb`"), Ok(r"a\tb".into()));
+    /// assert_eq!(Value::parse_literal("0o17"), Ok(15.into()));
+    /// ```
+    pub fn parse_literal(text: &str) -> Result<Value, LiteralError> {
+        match text {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "nil" => Ok(Value::Nil),
+            _ if text.starts_with('"') || text.starts_with('`') => {
+                let bytes = parse_string(text)
+                    .ok_or_else(|| LiteralError::InvalidSyntax(text.to_owned()))?;
+                String::from_utf8(bytes)
+                    .map(Value::from)
+                    .map_err(|_| LiteralError::InvalidUtf8(text.to_owned()))
+            }
+            _ if text.starts_with(|c: char| c.is_ascii_digit() || "+-.'".contains(c)) => {
+                Number::parse_go_literal(text).map(Value::Number)
+            }
+            _ => Err(LiteralError::InvalidSyntax(text.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Number, LiteralError> {
+        Number::parse_go_literal(s)
+    }
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(parse("0"), Ok(0.into()));
+        assert_eq!(parse("-0"), Ok(0.into()));
+        assert_eq!(parse("+5"), Ok(5.into()));
+        assert_eq!(parse("017"), Ok(15.into()));
+        assert_eq!(parse("0o17"), Ok(15.into()));
+        assert_eq!(parse("0O17"), Ok(15.into()));
+        assert_eq!(parse("0b101"), Ok(5.into()));
+        assert_eq!(parse("0X1f"), Ok(31.into()));
+        assert_eq!(parse("0x1e"), Ok(30.into()));
+        assert_eq!(parse("0x_1F"), Ok(31.into()));
+        assert_eq!(parse("0_17"), Ok(15.into()));
+        assert_eq!(parse("18446744073709551615"), Ok(u64::MAX.into()));
+        assert_eq!(parse("-9223372036854775808"), Ok(i64::MIN.into()));
+        assert!(!parse("1").unwrap().is_float());
+    }
+
+    #[test]
+    fn test_floats() {
+        let f = |s| parse(s).unwrap().as_f64().unwrap();
+        assert_eq!(f("1e3"), 1000.0);
+        assert_eq!(f("1E+3"), 1000.0);
+        assert_eq!(f("1.5"), 1.5);
+        assert_eq!(f(".5"), 0.5);
+        assert_eq!(f("5."), 5.0);
+        assert_eq!(f("-2.5e-3"), -0.0025);
+        assert_eq!(f("1_000.000_1"), 1000.0001);
+        assert_eq!(f("0x1p-2"), 0.25);
+        assert_eq!(f("0x1.8p1"), 3.0);
+        assert_eq!(f("0X.8P0"), 0.5);
+        assert_eq!(f("0x1p-1074"), 5e-324);
+        assert_eq!(f("0x1.fffffffffffffp1023"), f64::MAX);
+        assert_eq!(
+            f("0x1.0000000000000800000000000000001p0"),
+            1.0 + f64::EPSILON
+        );
+        assert_eq!(f("1e-400"), 0.0);
+        // Subnormals are rounded once, ties to even.
+        let sub = |bits: u64| f64::from_bits(bits);
+        assert_eq!(f("0x1.7ffffffffffffcp-1074"), sub(1));
+        assert_eq!(f("0x1.8p-1074"), sub(2));
+        assert_eq!(f("0x1.4p-1073"), sub(2));
+        assert_eq!(f("0x1p-1075"), 0.0);
+        assert_eq!(f("0x1.0000001p-1075"), sub(1));
+        assert_eq!(f("0x1p-1076"), 0.0);
+        assert_eq!(f("0x0.0000000000001p-1022"), sub(1));
+        assert_eq!(f("0x1.fffffffffffff8p-1023"), f64::MIN_POSITIVE);
+        assert_eq!(f("0x1.ffffffffffffep-1023"), sub((1 << 52) - 1));
+        assert!(f("-0.0").is_sign_negative());
+    }
+
+    #[test]
+    fn test_chars() {
+        assert_eq!(parse("'a'"), Ok(97.into()));
+        assert_eq!(parse("'ä'"), Ok(228.into()));
+        assert_eq!(parse(r"'\n'"), Ok(10.into()));
+        assert_eq!(parse(r"'\''"), Ok(39.into()));
+        assert_eq!(parse(r"'\x7f'"), Ok(127.into()));
+        assert_eq!(parse(r"'\377'"), Ok(255.into()));
+        assert_eq!(parse(r"'ä'"), Ok(228.into()));
+        assert_eq!(parse(r"'\U0001F600'"), Ok(0x1F600.into()));
+        for s in &[
+            "''",
+            "'ab'",
+            r"'\'",
+            r#"'\"'"#,
+            r"'\400'",
+            r"'\ud800'",
+            r"'\q'",
+            "'a",
+        ] {
+            assert_eq!(
+                parse(s),
+                Err(LiteralError::MalformedCharacter(s.to_string())),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            parse("'ab'").unwrap_err().to_string(),
+            "malformed character constant: 'ab'"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        for s in &[
+            "",
+            "-",
+            "1__0",
+            "_1",
+            "1_",
+            "0x",
+            "0xg",
+            "0b2",
+            "1e",
+            "1e+",
+            "1.2.3",
+            "0x1.5",
+            "1e400",
+            "0x1p",
+            "0o1.5",
+            "1i",
+            "nan",
+            "inf",
+            "0xFFFFFFFFFFFFFFFFF",
+            "1ä",
+            "0ä",
+            "-1ä",
+            "1.5ä",
+            "0x1pä",
+        ] {
+            assert_eq!(
+                parse(s),
+                Err(LiteralError::IllegalNumber(s.to_string())),
+                "{}",
+                s
+            );
+        }
+        for s in &[
+            "18446744073709551616",
+            "-9223372036854775809",
+            "09",
+            "0777777777777777777777777",
+        ] {
+            assert_eq!(
+                parse(s),
+                Err(LiteralError::IntegerOverflow(s.to_string())),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            parse("18446744073709551616").unwrap_err().to_string(),
+            "integer overflow: \"18446744073709551616\""
+        );
+        assert_eq!(
+            Value::parse_literal("1ä"),
+            Err(LiteralError::IllegalNumber("1ä".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(Value::parse_literal("false"), Ok(false.into()));
+        assert_eq!(Value::parse_literal("-1.5"), Ok((-1.5).into()));
+        assert_eq!(Value::parse_literal(r#""\x41ä\"""#), Ok("Aä\"".into()));
+        assert_eq!(Value::parse_literal(r#""\303\244""#), Ok("ä".into()));
+        assert_eq!(Value::parse_literal("`a\r\nb`"), Ok("a\nb".into()));
+        assert_eq!(Value::parse_literal(r#""""#), Ok("".into()));
+        assert_eq!(
+            Value::parse_literal(r#""\xff""#).unwrap_err().to_string(),
+            r#"string constant is not valid UTF-8: "\xff""#
+        );
+        assert_eq!(Value::parse_literal(r#""\xc3\xa4""#), Ok("ä".into()));
+        for s in &[r#""a"#, "\"a\nb\"", r#""\'""#, "`a", "``a`", "foo", "True"] {
+            assert_eq!(
+                Value::parse_literal(s),
+                Err(LiteralError::InvalidSyntax(s.to_string())),
+                "{}",
+                s
+            );
+        }
+    }
+}
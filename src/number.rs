@@ -1,20 +1,57 @@
 use std::cmp::{Ordering, PartialOrd};
+use std::convert::TryFrom;
 use std::fmt;
-use std::{f32, f64, i64, u64};
+use thiserror::Error;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+#[cfg(feature = "bigint")]
+use num_traits::{FromPrimitive, ToPrimitive};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+use crate::format;
+
+/// Errors returned by arithmetic on `Number`s.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ArithmeticError {
+    #[error("number overflow")]
+    Overflow,
+    #[error("division by zero")]
+    DivideByZero,
+}
 
 /// Internal number format for `gtmpl_value`.
+///
+/// Integers of up to 128 bits are represented exactly. With the `bigint`
+/// feature integers of any size are. With the `decimal` feature `Number` can
+/// hold exact decimals, which keep their scale (`1.50` stays `1.50`).
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Number {
     n: Num,
 }
 
+/// Name of the newtype struct numbers without a native serde representation
+/// are wrapped in, so `to_value` can tell them from strings. Formats like
+/// JSON write the plain string.
+#[cfg(feature = "serde")]
+pub(crate) const NUMBER_TOKEN: &str = "$gtmpl_value::private::Number";
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for Number {
+    /// Integers beyond 64 bits, big integers and decimals are serialized as
+    /// strings, so they survive formats like JSON and decimals keep their
+    /// scale.
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        self.n.serialize(serializer)
+        match self.n {
+            Num::U(n) => serializer.serialize_u64(n),
+            Num::I(n) => serializer.serialize_i64(n),
+            Num::F(n) => serializer.serialize_f64(n),
+            _ => serializer.serialize_newtype_struct(NUMBER_TOKEN, &self.to_string()),
+        }
     }
 }
 
@@ -30,17 +67,130 @@ impl<'de> serde::Deserialize<'de> for Number {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(untagged))]
-#[derive(Copy, Clone, Debug)]
+/// Integers always use the smallest fitting variant: `U` for non-negative
+/// and `I` for negative 64 bit integers, `U128` and `I128` beyond that and
+/// `Big` only beyond 128 bits. Decimals are never normalized.
+#[derive(Clone, Debug)]
 enum Num {
     U(u64),
     I(i64),
+    U128(u128),
+    I128(i128),
     F(f64),
+    #[cfg(feature = "bigint")]
+    Big(BigInt),
+    #[cfg(feature = "decimal")]
+    D(Decimal),
+}
+
+/// Sign (`true` if negative) and magnitude of an integer.
+type Parts = (bool, u128);
+
+/// 2^64, the smallest `f64` above the range of `u64`.
+const U64_END: f64 = 18_446_744_073_709_551_616.0;
+/// -2^63, the smallest `i64`.
+const I64_START: f64 = -9_223_372_036_854_775_808.0;
+/// 2^128, the smallest `f64` above the range of `u128`.
+const U128_END: f64 = 340_282_366_920_938_463_463_374_607_431_768_211_456.0;
+/// Magnitude of `i128::MIN`.
+const I128_MIN_MAGNITUDE: u128 = 1 << 127;
+/// Upper bound for the size of results of `Number::pow` on big integers.
+#[cfg(feature = "bigint")]
+const MAX_POW_BITS: u64 = 1 << 16;
+
+fn cmp_parts((a_neg, a): Parts, (b_neg, b): Parts) -> Ordering {
+    match (a_neg, b_neg) {
+        (false, false) => a.cmp(&b),
+        (true, true) => b.cmp(&a),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// Compares a float with an integer exactly, without rounding the integer.
+fn cmp_float_int(f: f64, i: Parts) -> Option<Ordering> {
+    let t = f.trunc();
+    if f.is_nan() {
+        None
+    } else if t >= U128_END {
+        Some(Ordering::Greater)
+    } else if t <= -U128_END {
+        Some(Ordering::Less)
+    } else {
+        match cmp_parts((t < 0.0, t.abs() as u128), i) {
+            Ordering::Equal => f.partial_cmp(&t),
+            o => Some(o),
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+fn cmp_float_big(f: f64, b: &BigInt) -> Option<Ordering> {
+    if f.is_nan() {
+        None
+    } else if f.is_infinite() {
+        Some(if f > 0.0 {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        })
+    } else {
+        let t = f.trunc();
+        match BigInt::from_f64(t)?.cmp(b) {
+            Ordering::Equal => f.partial_cmp(&t),
+            o => Some(o),
+        }
+    }
+}
+
+/// Converts a float to a decimal via its shortest representation, so `0.1`
+/// becomes `Decimal` `0.1`. All float to decimal conversions and comparisons
+/// use this rule.
+///
+/// Returns `None` for NaN, infinities and floats beyond the range or the
+/// precision of `Decimal`.
+#[cfg(feature = "decimal")]
+pub(crate) fn float_to_decimal(f: f64) -> Option<Decimal> {
+    let d: Decimal = f.to_string().parse().ok()?;
+    if d.is_zero() && f != 0.0 {
+        // Rounded away by the limited scale of `Decimal`.
+        return None;
+    }
+    Some(d)
+}
+
+/// Compares a decimal with any other number.
+///
+/// Floats are converted with `float_to_decimal` first. Floats and integers
+/// beyond the range of `Decimal` are larger in magnitude than any decimal,
+/// floats beyond its precision smaller than any non-zero decimal.
+#[cfg(feature = "decimal")]
+fn cmp_decimal(d: &Decimal, other: &Num) -> Option<Ordering> {
+    match *other {
+        Num::D(ref o) => Some(d.cmp(o)),
+        Num::F(f) if f.is_nan() => None,
+        Num::F(f) => Some(match float_to_decimal(f) {
+            Some(f) => d.cmp(&f),
+            None if f.abs() < 1.0 => match d.cmp(&Decimal::ZERO) {
+                Ordering::Equal if f > 0.0 => Ordering::Less,
+                Ordering::Equal => Ordering::Greater,
+                o => o,
+            },
+            None if f > 0.0 => Ordering::Less,
+            None => Ordering::Greater,
+        }),
+        ref o => Some(match o.to_decimal() {
+            Some(o) => d.cmp(&o),
+            None if o.is_negative() => Ordering::Greater,
+            None => Ordering::Less,
+        }),
+    }
 }
 
 /// `PartialOrd` for `Number`.
 ///
+/// Integers and floats are compared by their exact values.
+///
 /// # Examples
 ///
 /// ```rust
@@ -50,25 +200,34 @@ enum Num {
 /// let f: Number = 23.42.into();
 ///
 /// assert!(i < f);
+/// assert!(Number::from(u64::MAX) < Number::from(1e20));
 /// ```
 impl PartialOrd for Num {
     fn partial_cmp(&self, other: &Num) -> Option<Ordering> {
-        match (*self, *other) {
-            (Num::U(s), Num::U(o)) => s.partial_cmp(&o),
-            (Num::I(s), Num::I(o)) => s.partial_cmp(&o),
-            (Num::F(s), Num::F(o)) => s.partial_cmp(&o),
-            (Num::I(_), Num::U(_)) => Some(Ordering::Less),
-            (Num::U(_), Num::I(_)) => Some(Ordering::Greater),
-            (Num::F(s), Num::I(o)) => s.partial_cmp(&(o as f64)),
-            (Num::I(s), Num::F(o)) => (s as f64).partial_cmp(&o),
-            (Num::F(s), Num::U(o)) => s.partial_cmp(&(o as f64)),
-            (Num::U(s), Num::F(o)) => (s as f64).partial_cmp(&o),
+        match (self, other) {
+            (Num::F(s), Num::F(o)) => s.partial_cmp(o),
+            #[cfg(feature = "decimal")]
+            (Num::D(s), o) => cmp_decimal(s, o),
+            #[cfg(feature = "decimal")]
+            (s, Num::D(o)) => cmp_decimal(o, s).map(Ordering::reverse),
+            #[cfg(feature = "bigint")]
+            (Num::F(s), Num::Big(o)) => cmp_float_big(*s, o),
+            #[cfg(feature = "bigint")]
+            (Num::Big(s), Num::F(o)) => cmp_float_big(*o, s).map(Ordering::reverse),
+            #[cfg(feature = "bigint")]
+            (Num::Big(_), _) | (_, Num::Big(_)) => Some(self.to_bigint()?.cmp(&other.to_bigint()?)),
+            (Num::F(s), o) => cmp_float_int(*s, o.parts()?),
+            (s, Num::F(o)) => cmp_float_int(*o, s.parts()?).map(Ordering::reverse),
+            (s, o) => Some(cmp_parts(s.parts()?, o.parts()?)),
         }
     }
 }
 
 /// `PartialEq` for `Number`.
 ///
+/// Numbers are equal if their values are, regardless of their
+/// representation.
+///
 /// # Examples
 ///
 /// ```rust
@@ -78,19 +237,217 @@ impl PartialOrd for Num {
 /// let f: Number = 23.0.into();
 ///
 /// assert!(i == f);
+/// assert!(i == Number::float(23.0));
 /// ```
 impl PartialEq for Num {
     fn eq(&self, other: &Num) -> bool {
-        match (*self, *other) {
-            (Num::U(s), Num::U(o)) => s.eq(&o),
-            (Num::I(s), Num::I(o)) => s.eq(&o),
-            (Num::F(s), Num::F(o)) => s.eq(&o),
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl Num {
+    /// Sign and magnitude of an integer of up to 128 bits.
+    fn parts(&self) -> Option<Parts> {
+        match *self {
+            Num::U(n) => Some((false, u128::from(n))),
+            Num::I(n) => Some((n < 0, u128::from(n.unsigned_abs()))),
+            Num::U128(n) => Some((false, n)),
+            Num::I128(n) => Some((n < 0, n.unsigned_abs())),
+            _ => None,
+        }
+    }
+
+    fn from_parts((neg, n): Parts) -> Option<Num> {
+        if !neg {
+            Some(Num::from_u128(n))
+        } else if n <= I128_MIN_MAGNITUDE {
+            Some(Num::from_i128((n as i128).wrapping_neg()))
+        } else {
+            None
+        }
+    }
+
+    fn from_i128(n: i128) -> Num {
+        if let Ok(n) = u64::try_from(n) {
+            Num::U(n)
+        } else if let Ok(n) = i64::try_from(n) {
+            Num::I(n)
+        } else if n > 0 {
+            Num::U128(n as u128)
+        } else {
+            Num::I128(n)
+        }
+    }
+
+    fn from_u128(n: u128) -> Num {
+        match u64::try_from(n) {
+            Ok(n) => Num::U(n),
+            Err(_) => Num::U128(n),
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn from_bigint(n: BigInt) -> Num {
+        if let Some(i) = n.to_i128() {
+            Num::from_i128(i)
+        } else if let Some(u) = n.to_u128() {
+            Num::from_u128(u)
+        } else {
+            Num::Big(n)
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn to_bigint(&self) -> Option<BigInt> {
+        match *self {
+            Num::U(n) => Some(n.into()),
+            Num::I(n) => Some(n.into()),
+            Num::U128(n) => Some(n.into()),
+            Num::I128(n) => Some(n.into()),
+            Num::Big(ref n) => Some(n.clone()),
+            _ => None,
+        }
+    }
+
+    /// Converts decimals and integers within the range of `Decimal`.
+    #[cfg(feature = "decimal")]
+    fn to_decimal(&self) -> Option<Decimal> {
+        match *self {
+            Num::D(d) => Some(d),
+            ref n => {
+                let (neg, n) = n.parts()?;
+                let n = i128::try_from(n).ok()?;
+                Decimal::try_from_i128_with_scale(if neg { -n } else { n }, 0).ok()
+            }
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    fn is_negative(&self) -> bool {
+        match *self {
+            Num::I(_) | Num::I128(_) => true,
+            Num::F(n) => n < 0.0,
+            #[cfg(feature = "bigint")]
+            Num::Big(ref n) => n.sign() == num_bigint::Sign::Minus,
+            Num::D(d) => d.is_sign_negative() && !d.is_zero(),
             _ => false,
         }
     }
+
+    /// Parses the string representation of numbers that do not fit into a
+    /// serde number.
+    ///
+    /// Integer strings are parsed as integers, so decimals with a scale of 0
+    /// come back as integers.
+    #[cfg(feature = "serde")]
+    fn from_str(s: &str) -> Option<Num> {
+        if let Ok(n) = s.parse() {
+            return Some(Num::from_u128(n));
+        }
+        if let Ok(n) = s.parse() {
+            return Some(Num::from_i128(n));
+        }
+        #[cfg(feature = "bigint")]
+        {
+            if let Ok(n) = s.parse() {
+                return Some(Num::from_bigint(n));
+            }
+        }
+        #[cfg(feature = "decimal")]
+        {
+            if let Ok(d) = s.parse() {
+                return Some(Num::D(d));
+            }
+        }
+        None
+    }
+
+    /// Normalizes a float to an integer if that is lossless.
+    ///
+    /// NaN, infinities, `-0.0`, fractions and integers beyond the range of
+    /// `u64` and `i64` stay floats.
+    fn from_f64(n: f64) -> Num {
+        if n.fract() != 0.0 || (n == 0.0 && n.is_sign_negative()) {
+            // NaN and infinities have a NaN fraction.
+            Num::F(n)
+        } else if (0.0..U64_END).contains(&n) {
+            Num::U(n as u64)
+        } else if (I64_START..0.0).contains(&n) {
+            Num::I(n as i64)
+        } else {
+            Num::F(n)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Num {
+    fn deserialize<D>(deserializer: D) -> Result<Num, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct NumVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for NumVisitor {
+            type Value = Num;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a number")
+            }
+
+            fn visit_u64<E>(self, n: u64) -> Result<Num, E> {
+                Ok(Num::U(n))
+            }
+
+            fn visit_i64<E>(self, n: i64) -> Result<Num, E> {
+                Ok(Num::from_i128(i128::from(n)))
+            }
+
+            fn visit_u128<E>(self, n: u128) -> Result<Num, E> {
+                Ok(Num::from_u128(n))
+            }
+
+            fn visit_i128<E>(self, n: i128) -> Result<Num, E> {
+                Ok(Num::from_i128(n))
+            }
+
+            fn visit_f64<E>(self, n: f64) -> Result<Num, E> {
+                Ok(Num::F(n))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Num, E> {
+                Num::from_str(s)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(s), &self))
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Num, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+        }
+
+        deserializer.deserialize_any(NumVisitor)
+    }
 }
 
 impl Number {
+    /// Creates a float number, even if `n` is integral.
+    ///
+    /// `Number::from(f64)` turns integral floats into integers. Use this to
+    /// keep the float identity, e.g. for Go's type sensitive comparisons.
+    ///
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// assert_eq!(Number::float(2.0).as_f64(), Some(2.0));
+    /// assert_eq!(Number::from(2.0).as_f64(), None);
+    /// ```
+    pub fn float(n: f64) -> Number {
+        Number { n: Num::F(n) }
+    }
+
     /// ```rust
     /// use std::i64;
     /// use gtmpl_value::Number;
@@ -138,6 +495,66 @@ impl Number {
         }
     }
 
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// let big: Number = (-1i128 << 100).into();
+    ///
+    /// assert_eq!(big.as_i128(), Some(-1 << 100));
+    /// assert!(big.as_u128().is_none());
+    /// assert!(big.as_i64().is_none());
+    /// assert!(Number::from(u128::MAX).as_i128().is_none());
+    /// ```
+    pub fn as_i128(&self) -> Option<i128> {
+        match self.n {
+            Num::U(n) => Some(i128::from(n)),
+            Num::I(n) => Some(i128::from(n)),
+            Num::U128(n) => i128::try_from(n).ok(),
+            Num::I128(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// let big: Number = u128::MAX.into();
+    ///
+    /// assert_eq!(big.as_u128(), Some(u128::MAX));
+    /// assert!(big.as_u64().is_none());
+    /// assert!(Number::from(-1).as_u128().is_none());
+    /// ```
+    pub fn as_u128(&self) -> Option<u128> {
+        match self.n {
+            Num::U(n) => Some(u128::from(n)),
+            Num::U128(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer as a `BigInt`, `None` for floats and decimals.
+    #[cfg(feature = "bigint")]
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        self.n.to_bigint()
+    }
+
+    /// Returns decimals and integers within the range of `Decimal` as a
+    /// `Decimal`, `None` otherwise.
+    ///
+    /// ```rust
+    /// use gtmpl_value::Number;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let d: Number = Decimal::new(150, 2).into();
+    /// assert_eq!(d.to_decimal(), Some(Decimal::new(150, 2)));
+    /// assert_eq!(Number::from(3).to_decimal(), Some(Decimal::from(3)));
+    /// assert_eq!(Number::from(0.5).to_decimal(), None);
+    /// ```
+    #[cfg(feature = "decimal")]
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        self.n.to_decimal()
+    }
+
     /// ```rust
     /// use std::i64;
     /// use gtmpl_value::Number;
@@ -154,6 +571,354 @@ impl Number {
             _ => None,
         }
     }
+
+    /// Returns `true` if serde has a native representation for the number:
+    /// 64 bit integers and floats.
+    #[cfg(feature = "serde")]
+    pub(crate) fn is_native(&self) -> bool {
+        matches!(self.n, Num::U(_) | Num::I(_) | Num::F(_))
+    }
+
+    /// Parses the string a number without a native serde representation is
+    /// serialized as.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_serialized(s: &str) -> Option<Number> {
+        Num::from_str(s).map(|n| Number { n })
+    }
+
+    /// Returns `true` if the number is a floating point number.
+    pub(crate) fn is_float(&self) -> bool {
+        matches!(self.n, Num::F(_))
+    }
+
+    /// Returns `true` if the number is an exact decimal.
+    #[cfg(feature = "decimal")]
+    pub(crate) fn is_decimal(&self) -> bool {
+        matches!(self.n, Num::D(_))
+    }
+
+    /// Returns a Go like name of the number's type.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self.n {
+            Num::F(_) => "float64",
+            #[cfg(feature = "decimal")]
+            Num::D(_) => "decimal",
+            _ => "int",
+        }
+    }
+
+    /// Returns `true` if the number is zero (including `-0.0`).
+    ///
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// assert!(Number::from(0).is_zero());
+    /// assert!(Number::from(-0.0).is_zero());
+    /// assert!(!Number::from(0.1).is_zero());
+    /// ```
+    pub fn is_zero(&self) -> bool {
+        match self.n {
+            Num::F(n) => n == 0.0,
+            #[cfg(feature = "decimal")]
+            Num::D(n) => n.is_zero(),
+            ref n => n.parts().is_some_and(|(_, n)| n == 0),
+        }
+    }
+}
+
+/// Binary arithmetic operations.
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Op {
+    fn parts(self, (a_neg, a): Parts, (b_neg, b): Parts) -> Option<Parts> {
+        match self {
+            Op::Add if a_neg == b_neg => Some((a_neg, a.checked_add(b)?)),
+            Op::Add if a >= b => Some((a_neg, a - b)),
+            Op::Add => Some((b_neg, b - a)),
+            Op::Sub => Op::Add.parts((a_neg, a), (!b_neg, b)),
+            Op::Mul => Some((a_neg != b_neg, a.checked_mul(b)?)),
+            Op::Div => Some((a_neg != b_neg, a / b)),
+            Op::Rem => Some((a_neg, a % b)),
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big(self, a: BigInt, b: BigInt) -> BigInt {
+        match self {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+            Op::Rem => a % b,
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    fn decimal(self, a: Decimal, b: Decimal) -> Option<Decimal> {
+        match self {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div => a.checked_div(b),
+            Op::Rem => a.checked_rem(b),
+        }
+    }
+
+    fn float(self, a: f64, b: f64) -> f64 {
+        match self {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+            Op::Rem => a % b,
+        }
+    }
+}
+
+/// Checked arithmetic.
+///
+/// Integer operations are carried out exactly and fail with
+/// `ArithmeticError::Overflow` if the result does not fit into a `Number`.
+/// As in Go, integer division truncates towards zero. Decimals are combined
+/// with decimals, integers and floats exactly, converting floats via their
+/// shortest representation like comparisons do (`0.2` becomes `0.2`). Other
+/// operations with a float operand, including those with floats that do not
+/// fit into a `Decimal`, are performed on `f64`, failing if a finite input
+/// produces an infinite result. Dividing by zero is always an error.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{ArithmeticError, Number};
+///
+/// let a: Number = 7.into();
+/// assert_eq!(a.checked_sub(&10.into()), Ok((-3).into()));
+/// assert_eq!(a.checked_div(&2.into()), Ok(3.into()));
+/// assert_eq!(a.checked_mul(&0.5.into()), Ok(3.5.into()));
+/// assert_eq!(a.checked_rem(&0.into()), Err(ArithmeticError::DivideByZero));
+/// assert_eq!(Number::from(u64::MAX).checked_add(&1.into()), Ok((1u128 << 64).into()));
+/// ```
+impl Number {
+    pub(crate) fn to_f64(&self) -> f64 {
+        match self.n {
+            Num::U(n) => n as f64,
+            Num::I(n) => n as f64,
+            Num::U128(n) => n as f64,
+            Num::I128(n) => n as f64,
+            Num::F(n) => n,
+            #[cfg(feature = "bigint")]
+            Num::Big(ref n) => n.to_f64().unwrap_or(f64::NAN),
+            #[cfg(feature = "decimal")]
+            Num::D(n) => rust_decimal::prelude::ToPrimitive::to_f64(&n).unwrap_or(f64::NAN),
+        }
+    }
+
+    fn from_float_result(a: f64, b: f64, r: f64) -> Result<Number, ArithmeticError> {
+        if r.is_infinite() && a.is_finite() && b.is_finite() {
+            Err(ArithmeticError::Overflow)
+        } else {
+            Ok(Number::float(r))
+        }
+    }
+
+    fn checked_op(&self, other: &Number, op: Op) -> Result<Number, ArithmeticError> {
+        #[cfg(feature = "decimal")]
+        {
+            if self.is_decimal() || other.is_decimal() {
+                let to_decimal = |n: &Number| match n.n {
+                    Num::F(f) => float_to_decimal(f),
+                    ref n => n.to_decimal(),
+                };
+                match (to_decimal(self), to_decimal(other)) {
+                    (Some(a), Some(b)) => {
+                        return op
+                            .decimal(a, b)
+                            .map(|n| Number { n: Num::D(n) })
+                            .ok_or(ArithmeticError::Overflow)
+                    }
+                    _ if self.is_float() || other.is_float() => {}
+                    _ => return Err(ArithmeticError::Overflow),
+                }
+            }
+        }
+        if self.is_float() || other.is_float() {
+            let (a, b) = (self.to_f64(), other.to_f64());
+            return Number::from_float_result(a, b, op.float(a, b));
+        }
+        if let (Some(a), Some(b)) = (self.n.parts(), other.n.parts()) {
+            if let Some(n) = op.parts(a, b).and_then(Num::from_parts) {
+                return Ok(Number { n });
+            }
+        }
+        #[cfg(feature = "bigint")]
+        {
+            if let (Some(a), Some(b)) = (self.to_bigint(), other.to_bigint()) {
+                return Ok(Number {
+                    n: Num::from_bigint(op.big(a, b)),
+                });
+            }
+        }
+        Err(ArithmeticError::Overflow)
+    }
+
+    /// Adds two numbers.
+    pub fn checked_add(&self, other: &Number) -> Result<Number, ArithmeticError> {
+        self.checked_op(other, Op::Add)
+    }
+
+    /// Subtracts `other` from `self`.
+    pub fn checked_sub(&self, other: &Number) -> Result<Number, ArithmeticError> {
+        self.checked_op(other, Op::Sub)
+    }
+
+    /// Multiplies two numbers.
+    pub fn checked_mul(&self, other: &Number) -> Result<Number, ArithmeticError> {
+        self.checked_op(other, Op::Mul)
+    }
+
+    /// Divides `self` by `other`.
+    pub fn checked_div(&self, other: &Number) -> Result<Number, ArithmeticError> {
+        if other.is_zero() {
+            return Err(ArithmeticError::DivideByZero);
+        }
+        self.checked_op(other, Op::Div)
+    }
+
+    /// Remainder of `self` divided by `other`, with the sign of `self`.
+    pub fn checked_rem(&self, other: &Number) -> Result<Number, ArithmeticError> {
+        if other.is_zero() {
+            return Err(ArithmeticError::DivideByZero);
+        }
+        self.checked_op(other, Op::Rem)
+    }
+
+    /// Negates the number.
+    ///
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// assert_eq!(Number::from(i64::MIN).neg(), Ok((i64::MAX as u64 + 1).into()));
+    /// assert_eq!(Number::from(u64::MAX).neg(), Ok((-i128::from(u64::MAX)).into()));
+    /// ```
+    pub fn neg(&self) -> Result<Number, ArithmeticError> {
+        match self.n {
+            Num::F(n) => Ok(Number::float(-n)),
+            #[cfg(feature = "decimal")]
+            Num::D(n) => Ok(Number { n: Num::D(-n) }),
+            _ => Number::from(0).checked_sub(self),
+        }
+    }
+
+    /// Raises `self` to the power of `exp`.
+    ///
+    /// The result is an integer if both operands are integers and `exp` is
+    /// not negative, a float if either operand is a float or `exp` is
+    /// negative. Decimals raised to an integer are decimals; negative
+    /// exponents divide one by the positive power.
+    ///
+    /// ```rust
+    /// use gtmpl_value::Number;
+    ///
+    /// assert_eq!(Number::from(-3).pow(&3.into()), Ok((-27).into()));
+    /// assert_eq!(Number::from(2).pow(&(-1).into()), Ok(0.5.into()));
+    /// assert_eq!(Number::from(2).pow(&64.into()), Ok((1u128 << 64).into()));
+    /// ```
+    pub fn pow(&self, exp: &Number) -> Result<Number, ArithmeticError> {
+        let (neg, mag) = match exp.exp_parts() {
+            Some(parts) if !self.is_float() => parts,
+            _ => return self.float_pow(exp),
+        };
+        #[cfg(feature = "decimal")]
+        {
+            if let Num::D(d) = self.n {
+                let d = decimal_pow(d, mag);
+                let d = if neg {
+                    d.and_then(|d| Decimal::ONE.checked_div(d))
+                } else {
+                    d
+                };
+                return d
+                    .map(|n| Number { n: Num::D(n) })
+                    .ok_or(ArithmeticError::Overflow);
+            }
+        }
+        if neg {
+            return self.float_pow(exp);
+        }
+        // Powers of 0, 1 and -1 never overflow, whatever the exponent.
+        match self.n.parts() {
+            Some((_, 0)) if mag > 0 => return Ok(0.into()),
+            Some((_, 0)) | Some((false, 1)) => return Ok(1.into()),
+            Some((true, 1)) => return Ok(if mag % 2 == 0 { 1 } else { -1 }.into()),
+            _ => {}
+        }
+        let exp = u32::try_from(mag).map_err(|_| ArithmeticError::Overflow)?;
+        let n = self.n.parts().and_then(|(neg, n)| {
+            let n = n.checked_pow(exp)?;
+            Num::from_parts((neg && exp % 2 == 1, n))
+        });
+        match n {
+            Some(n) => Ok(Number { n }),
+            None => self.big_pow(exp),
+        }
+    }
+
+    fn float_pow(&self, exp: &Number) -> Result<Number, ArithmeticError> {
+        let (a, b) = (self.to_f64(), exp.to_f64());
+        Number::from_float_result(a, b, a.powf(b))
+    }
+
+    /// Sign and magnitude of an integer exponent. Bigint magnitudes saturate
+    /// to `u128::MAX` or `u128::MAX - 1`, keeping their parity.
+    fn exp_parts(&self) -> Option<Parts> {
+        match self.n {
+            #[cfg(feature = "bigint")]
+            Num::Big(ref n) => Some((
+                n.sign() == num_bigint::Sign::Minus,
+                u128::MAX - u128::from(!n.bit(0)),
+            )),
+            ref n => n.parts(),
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big_pow(&self, exp: u32) -> Result<Number, ArithmeticError> {
+        let base = self.to_bigint().ok_or(ArithmeticError::Overflow)?;
+        if base.bits().saturating_mul(u64::from(exp)) > MAX_POW_BITS {
+            return Err(ArithmeticError::Overflow);
+        }
+        Ok(Number {
+            n: Num::from_bigint(base.pow(exp)),
+        })
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    fn big_pow(&self, _exp: u32) -> Result<Number, ArithmeticError> {
+        Err(ArithmeticError::Overflow)
+    }
+}
+
+/// Exponentiation by squaring.
+#[cfg(feature = "decimal")]
+fn decimal_pow(mut base: Decimal, mut exp: u128) -> Option<Decimal> {
+    let mut acc = Decimal::ONE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = acc.checked_mul(base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(acc)
 }
 
 impl fmt::Display for Number {
@@ -161,11 +926,51 @@ impl fmt::Display for Number {
         match self.n {
             Num::U(n) => write!(f, "{}", n),
             Num::I(n) => write!(f, "{}", n),
-            Num::F(n) => write!(f, "{}", n),
+            Num::U128(n) => write!(f, "{}", n),
+            Num::I128(n) => write!(f, "{}", n),
+            Num::F(n) => format::write_float(f, n),
+            #[cfg(feature = "bigint")]
+            Num::Big(ref n) => write!(f, "{}", n),
+            #[cfg(feature = "decimal")]
+            Num::D(ref n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl From<i128> for Number {
+    fn from(n: i128) -> Self {
+        Number {
+            n: Num::from_i128(n),
         }
     }
 }
 
+impl From<u128> for Number {
+    fn from(n: u128) -> Self {
+        Number {
+            n: Num::from_u128(n),
+        }
+    }
+}
+
+/// Converts a big integer, using a primitive representation if it fits.
+#[cfg(feature = "bigint")]
+impl From<BigInt> for Number {
+    fn from(n: BigInt) -> Self {
+        Number {
+            n: Num::from_bigint(n),
+        }
+    }
+}
+
+/// Converts a decimal, keeping its scale.
+#[cfg(feature = "decimal")]
+impl From<Decimal> for Number {
+    fn from(n: Decimal) -> Self {
+        Number { n: Num::D(n) }
+    }
+}
+
 macro_rules! from_i {
     ($($ty:ident)*) => {
         $(
@@ -222,34 +1027,42 @@ impl From<usize> for Number {
     }
 }
 
-macro_rules! from_f {
-    ($($ty:ident)*) => {
-        $(
-            impl From<$ty> for Number {
-                fn from(n: $ty) -> Self {
-                    let num = match n {
-                        n if n.fract().abs() < $ty::EPSILON => {
-                            if n.is_sign_negative() { Num::I(n as i64) } else { Num::U(n as u64) }
-                        },
-                        n => Num::F(f64::from(n)),
-                    };
-                    Number {
-                        n: num,
-                    }
-                }
-            }
-        )*
-    };
+/// Converts a float into a `Number`, normalizing it to an integer only if
+/// that is lossless.
+///
+/// ```rust
+/// use gtmpl_value::Number;
+///
+/// assert_eq!(Number::from(3.0).as_u64(), Some(3));
+/// assert_eq!(Number::from(1e20).as_f64(), Some(1e20));
+/// assert_eq!(Number::from(-0.0).as_f64(), Some(-0.0));
+/// assert!(Number::from(f64::NAN).as_f64().unwrap().is_nan());
+/// ```
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Number {
+            n: Num::from_f64(n),
+        }
+    }
 }
 
-from_f!(
-    f64 f32
-);
+/// Converts an `f32` via its shortest decimal representation, so `0.1f32`
+/// becomes `0.1` instead of `0.10000000149011612`.
+impl From<f32> for Number {
+    fn from(n: f32) -> Self {
+        let f = if n.is_finite() {
+            n.to_string().parse().unwrap_or_else(|_| f64::from(n))
+        } else {
+            f64::from(n)
+        };
+        Number::from(f)
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::u64;
+    use crate::value::Value;
 
     #[test]
     fn test_i() {
@@ -287,6 +1100,90 @@ mod test {
         assert_eq!(num.as_f64(), Some(-23.42f64));
     }
 
+    #[test]
+    fn test_display_float() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "-0"),
+            (1.0, "1"),
+            (-2.5, "-2.5"),
+            (0.1, "0.1"),
+            (0.0001, "0.0001"),
+            (0.00001, "1e-05"),
+            (1e-7, "1e-07"),
+            (123456.0, "123456"),
+            (1234567.0, "1.234567e+06"),
+            (1e6, "1e+06"),
+            (100000.5, "100000.5"),
+            (1e21, "1e+21"),
+            (1.5e300, "1.5e+300"),
+            (5e-324, "5e-324"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (1.0 / 3.0, "0.3333333333333333"),
+            (f64::NAN, "NaN"),
+            (f64::INFINITY, "+Inf"),
+            (f64::NEG_INFINITY, "-Inf"),
+        ];
+        for &(f, s) in &cases {
+            assert_eq!(Number::float(f).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_from_float() {
+        assert_eq!(Number::from(1e20).n, Num::F(1e20));
+        assert_eq!(Number::from(1e20).to_string(), "1e+20");
+        assert!(matches!(Number::from(-0.0).n, Num::F(n) if n.is_sign_negative()));
+        assert!(matches!(Number::from(0.0).n, Num::U(0)));
+        assert!(matches!(Number::from(-3.0).n, Num::I(-3)));
+        assert!(matches!(Number::from(U64_END - 2048.0).n, Num::U(_)));
+        assert!(matches!(Number::from(U64_END).n, Num::F(_)));
+        assert!(matches!(Number::from(I64_START).n, Num::I(i64::MIN)));
+        assert!(matches!(Number::from(I64_START * 2.0).n, Num::F(_)));
+        assert!(matches!(Number::from(f64::INFINITY).n, Num::F(n) if n == f64::INFINITY));
+        assert!(matches!(Number::from(f64::NEG_INFINITY).n, Num::F(n) if n == f64::NEG_INFINITY));
+        assert!(matches!(Number::from(f64::NAN).n, Num::F(n) if n.is_nan()));
+        assert!(matches!(Number::from(1e-300).n, Num::F(_)));
+    }
+
+    #[test]
+    fn test_from_f32() {
+        assert_eq!(Number::from(0.1f32).as_f64(), Some(0.1));
+        assert_eq!(Number::from(3.0f32).as_u64(), Some(3));
+        assert_eq!(Number::from(16777216f32).as_u64(), Some(16777216));
+        assert_eq!(Number::from(f32::MAX).as_f64(), Some(3.4028235e38));
+        assert_eq!(
+            Number::from(f32::NEG_INFINITY).as_f64(),
+            Some(f64::NEG_INFINITY)
+        );
+    }
+
+    #[test]
+    fn test_float_identity() {
+        let f = Number::float(2.0);
+        assert!(f.is_float());
+        assert_eq!(f, Number::from(2));
+        assert_eq!(f.to_string(), "2");
+        assert_eq!(Number::float(1e20), Number::from(1e20));
+    }
+
+    #[test]
+    fn test_cmp_exact() {
+        let max: Number = u64::MAX.into();
+        assert!(max < Number::float(U64_END));
+        assert_ne!(max, Number::float(U64_END));
+        assert!(Number::from(i64::MIN) == Number::float(I64_START));
+        assert!(Number::from(2) < Number::float(2.5));
+        assert!(Number::from(-2) > Number::float(-2.5));
+        assert!(Number::float(f64::INFINITY) > max);
+        assert!(Number::float(f64::NEG_INFINITY) < Number::from(i64::MIN));
+        assert!(Number::float(f64::NAN)
+            .partial_cmp(&Number::from(1))
+            .is_none());
+        assert_ne!(Number::float(f64::NAN), Number::float(f64::NAN));
+        assert_eq!(Number::from(-0.0), Number::from(0));
+    }
+
     #[test]
     fn test_le() {
         let a: Number = 23.0f64.into();
@@ -294,10 +1191,364 @@ mod test {
         assert!(a <= b);
     }
 
+    #[test]
+    fn test_cmp_signed_unsigned() {
+        let a: Number = (-5i64).into();
+        let b: Number = 3u64.into();
+        assert!(a < b);
+        assert!(b > a);
+        let a = Number { n: Num::I(5) };
+        assert!(a > b);
+        assert!(b < a);
+        assert_eq!(Number { n: Num::I(3) }, b);
+    }
+
     #[test]
     fn test_ge() {
         let a: Number = 1u64.into();
         let b: Number = (-1i64).into();
         assert!(a > b);
     }
+
+    #[test]
+    fn test_checked_add() {
+        let a: Number = 1u64.into();
+        assert_eq!(a.checked_add(&(-3).into()), Ok((-2).into()));
+        assert_eq!(a.checked_add(&0.5.into()), Ok(1.5.into()));
+        assert_eq!(Number::from(0.5).checked_add(&0.5.into()), Ok(1.into()));
+        let min: Number = i64::MIN.into();
+        assert_eq!(
+            min.checked_add(&(-1).into()),
+            Ok((i128::from(i64::MIN) - 1).into())
+        );
+        let max: Number = u128::MAX.into();
+        assert_eq!(max.checked_add(&(-1).into()), Ok((u128::MAX - 1).into()));
+        #[cfg(not(feature = "bigint"))]
+        assert_eq!(max.checked_add(&1.into()), Err(ArithmeticError::Overflow));
+        #[cfg(feature = "bigint")]
+        assert_eq!(
+            max.checked_add(&1.into()).unwrap().to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        let max: Number = f64::MAX.into();
+        assert_eq!(
+            max.checked_add(&f64::MAX.into()),
+            Err(ArithmeticError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        let max: Number = u64::MAX.into();
+        let min: Number = i64::MIN.into();
+        assert_eq!(max.checked_sub(&max), Ok(0.into()));
+        assert_eq!(
+            min.checked_sub(&max),
+            Ok((i128::from(i64::MIN) - i128::from(u64::MAX)).into())
+        );
+        let min: Number = i128::MIN.into();
+        assert_eq!(min.checked_sub(&min), Ok(0.into()));
+        assert_eq!(Number::from(0).checked_sub(&min), Ok((1u128 << 127).into()));
+        #[cfg(not(feature = "bigint"))]
+        assert_eq!(min.checked_sub(&1.into()), Err(ArithmeticError::Overflow));
+        #[cfg(feature = "bigint")]
+        assert!(min.checked_sub(&1.into()).unwrap() < min);
+        assert_eq!(Number::from(0u8).checked_sub(&1u8.into()), Ok((-1).into()));
+    }
+
+    #[test]
+    fn test_checked_mul() {
+        let a: Number = (-4).into();
+        assert_eq!(a.checked_mul(&(-4).into()), Ok(16.into()));
+        assert_eq!(a.checked_mul(&2.5.into()), Ok((-10).into()));
+        let max: Number = u64::MAX.into();
+        assert_eq!(
+            max.checked_mul(&(-2).into()),
+            Ok((-2 * i128::from(u64::MAX)).into())
+        );
+        let max: Number = u128::MAX.into();
+        #[cfg(not(feature = "bigint"))]
+        assert_eq!(max.checked_mul(&2.into()), Err(ArithmeticError::Overflow));
+        #[cfg(feature = "bigint")]
+        assert_eq!(
+            max.checked_mul(&2.into()).unwrap().checked_div(&max),
+            Ok(2.into())
+        );
+    }
+
+    #[test]
+    fn test_checked_div_rem() {
+        let a: Number = (-7).into();
+        assert_eq!(a.checked_div(&2.into()), Ok((-3).into()));
+        assert_eq!(a.checked_rem(&2.into()), Ok((-1).into()));
+        assert_eq!(a.checked_rem(&(-2.5).into()), Ok((-2).into()));
+        assert_eq!(a.checked_div(&0.into()), Err(ArithmeticError::DivideByZero));
+        assert_eq!(
+            a.checked_div(&(-0.0).into()),
+            Err(ArithmeticError::DivideByZero)
+        );
+        let min: Number = i64::MIN.into();
+        assert_eq!(
+            min.checked_div(&(-1).into()),
+            Ok((i64::MAX as u64 + 1).into())
+        );
+        let min: Number = i128::MIN.into();
+        assert_eq!(min.checked_div(&(-1).into()), Ok((1u128 << 127).into()));
+        assert_eq!(min.checked_rem(&(-7).into()), Ok((-2).into()));
+        let max: Number = u128::MAX.into();
+        #[cfg(not(feature = "bigint"))]
+        assert_eq!(
+            max.checked_div(&(-1).into()),
+            Err(ArithmeticError::Overflow)
+        );
+        #[cfg(feature = "bigint")]
+        assert_eq!(max.checked_div(&(-1).into()), max.neg());
+        assert_eq!(max.checked_rem(&(-10).into()), Ok(5.into()));
+    }
+
+    #[test]
+    fn test_neg_pow() {
+        assert_eq!(Number::from(0).neg(), Ok(0.into()));
+        assert_eq!(Number::from(1.5).neg(), Ok((-1.5).into()));
+        assert_eq!(Number::from(2).pow(&10.into()), Ok(1024.into()));
+        assert_eq!(Number::from(4).pow(&0.5.into()), Ok(2.into()));
+        assert_eq!(
+            Number::from(2).pow(&u64::MAX.into()),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            Number::from(2).pow(&u64::from(u32::MAX).into()),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            Number::float(10.0).pow(&400.into()),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(Number::from(-2).pow(&127.into()), Ok(i128::MIN.into()));
+        assert_eq!(
+            Number::from(u64::MAX).neg(),
+            Ok((-i128::from(u64::MAX)).into())
+        );
+        #[cfg(not(feature = "bigint"))]
+        assert_eq!(
+            Number::from(2).pow(&128.into()),
+            Err(ArithmeticError::Overflow)
+        );
+        #[cfg(feature = "bigint")]
+        assert_eq!(
+            Number::from(2).pow(&128.into()),
+            Number::from(u128::MAX).checked_add(&1.into())
+        );
+    }
+
+    #[test]
+    fn test_float_results() {
+        let sum = Number::from(1.5).checked_add(&0.5.into()).unwrap();
+        assert!(sum.is_float());
+        assert_eq!(sum.to_string(), "2");
+        assert!(Value::Number(sum).go_lt(&2.5.into()).unwrap());
+        assert!(Number::float(2.0).neg().unwrap().is_float());
+        assert!(Number::from(2).checked_mul(&1.5.into()).unwrap().is_float());
+        assert!(Number::from(4).pow(&0.5.into()).unwrap().is_float());
+        assert!(!Number::from(2).pow(&2.into()).unwrap().is_float());
+    }
+
+    #[test]
+    fn test_pow_trivial_bases() {
+        let huge: Number = (1u64 << 40).into();
+        assert_eq!(Number::from(1).pow(&huge), Ok(1.into()));
+        assert_eq!(Number::from(0).pow(&huge), Ok(0.into()));
+        assert_eq!(Number::from(0).pow(&0.into()), Ok(1.into()));
+        assert_eq!(Number::from(-1).pow(&huge), Ok(1.into()));
+        assert_eq!(Number::from(-1).pow(&u64::MAX.into()), Ok((-1).into()));
+        assert!(!Number::from(1).pow(&huge).unwrap().is_float());
+        let wide: Number = (u128::MAX - 1).into();
+        assert_eq!(Number::from(1).pow(&wide), Ok(1.into()));
+        assert_eq!(Number::from(-1).pow(&wide), Ok(1.into()));
+        assert_eq!(Number::from(2).pow(&wide), Err(ArithmeticError::Overflow));
+    }
+
+    #[test]
+    fn test_128() {
+        let max: Number = u128::MAX.into();
+        let min: Number = i128::MIN.into();
+        assert!(matches!(max.n, Num::U128(u128::MAX)));
+        assert!(matches!(min.n, Num::I128(i128::MIN)));
+        assert!(matches!(
+            Number::from(u128::from(u64::MAX)).n,
+            Num::U(u64::MAX)
+        ));
+        assert!(matches!(Number::from(-1i128).n, Num::I(-1)));
+        assert_eq!(max.to_string(), "340282366920938463463374607431768211455");
+        assert_eq!(min.to_string(), "-170141183460469231731687303715884105728");
+        assert_eq!(max.as_i128(), None);
+        assert_eq!(Number::from(1u128 << 100).as_i128(), Some(1 << 100));
+        assert_eq!(min.as_u128(), None);
+        assert_eq!(max.type_name(), "int");
+        assert!(!max.is_zero());
+        assert_eq!(max.to_f64(), u128::MAX as f64);
+    }
+
+    #[test]
+    fn test_cmp_128() {
+        let max: Number = u128::MAX.into();
+        let min: Number = i128::MIN.into();
+        assert!(min < Number::from(i64::MIN));
+        assert!(max > Number::from(u64::MAX));
+        assert!(min < max);
+        assert!(max < Number::float(U128_END));
+        assert!(max > Number::float(U128_END / 2.0));
+        assert_eq!(min, Number::float(-U128_END / 2.0));
+        assert!(min > Number::float(-U128_END));
+        assert!(Number::float(f64::MAX) > max);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_bigint() {
+        let big = Number::from(u128::MAX).checked_mul(&4.into()).unwrap();
+        assert!(matches!(big.n, Num::Big(_)));
+        assert_eq!(big.to_string(), "1361129467683753853853498429727072845820");
+        assert!(big > Number::from(u128::MAX));
+        assert!(big.neg().unwrap() < Number::from(i128::MIN));
+        assert!(big < Number::float(1e40));
+        assert!(big > Number::float(1e39));
+        assert!(big < Number::float(f64::INFINITY));
+        assert_eq!(big.as_u128(), None);
+        assert_eq!(big.type_name(), "int");
+        assert_eq!(big.checked_div(&4.into()), Ok(u128::MAX.into()));
+        assert_eq!(Number::from(BigInt::from(-5)), Number::from(-5));
+        assert!(matches!(Number::from(BigInt::from(-5)).n, Num::I(-5)));
+        assert_eq!(big.to_bigint(), Some(BigInt::from(u128::MAX) * 4));
+        assert_eq!(
+            Number::from(2).pow(&100_000.into()),
+            Err(ArithmeticError::Overflow)
+        );
+        let odd = big.checked_add(&1.into()).unwrap();
+        assert_eq!(Number::from(-1).pow(&big), Ok(1.into()));
+        assert_eq!(Number::from(-1).pow(&odd), Ok((-1).into()));
+        assert_eq!(Number::from(2).pow(&big), Err(ArithmeticError::Overflow));
+        assert_eq!(Number::from(2).pow(&big.neg().unwrap()), Ok(0.0.into()));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal() {
+        let d = |n, scale| Number::from(Decimal::new(n, scale));
+        assert_eq!(d(1, 1).checked_add(&d(2, 1)), Ok(d(3, 1)));
+        assert_eq!(d(1, 1).checked_add(&d(2, 1)).unwrap().to_string(), "0.3");
+        assert_eq!(d(150, 2).to_string(), "1.50");
+        assert_eq!(
+            d(150, 2).checked_mul(&2.into()).unwrap().to_string(),
+            "3.00"
+        );
+        assert_eq!(
+            d(1, 0).checked_div(&3.into()).unwrap().to_string(),
+            "0.3333333333333333333333333333"
+        );
+        assert_eq!(d(75, 1).checked_rem(&2.into()), Ok(d(15, 1)));
+        assert_eq!(d(1, 1).checked_add(&0.5.into()), Ok(d(6, 1)));
+        assert_eq!(d(1, 1).checked_add(&0.2.into()), Ok(d(3, 1)));
+        assert_eq!(Number::float(0.2).checked_add(&d(1, 1)), Ok(d(3, 1)));
+        let literal = Number::parse_go_literal("0.2").unwrap();
+        assert_eq!(d(1, 1).checked_add(&literal).unwrap().to_string(), "0.3");
+        assert_eq!(
+            d(1, 1).checked_mul(&0.2.into()).unwrap().to_string(),
+            "0.02"
+        );
+        assert!(d(1, 1).checked_add(&1e-30.into()).unwrap().is_float());
+        assert!(d(1, 1)
+            .checked_add(&f64::NAN.into())
+            .unwrap()
+            .to_f64()
+            .is_nan());
+        assert_eq!(
+            d(1, 1).checked_div(&0.into()),
+            Err(ArithmeticError::DivideByZero)
+        );
+        assert_eq!(
+            d(1, 1).checked_add(&u128::MAX.into()),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(d(15, 1).pow(&3.into()).unwrap().to_string(), "3.375");
+        assert_eq!(d(15, 1).neg().unwrap().to_string(), "-1.5");
+        assert_eq!(d(2, 0).pow(&200.into()), Err(ArithmeticError::Overflow));
+        assert_eq!(
+            d(15, 1).pow(&(-1).into()).unwrap().to_string(),
+            "0.6666666666666666666666666667"
+        );
+        assert_eq!(d(2, 0).pow(&(-2).into()), Ok(d(25, 2)));
+        assert_eq!(d(1, 0).pow(&u128::MAX.into()), Ok(d(1, 0)));
+        assert_eq!(d(0, 0).pow(&(-1).into()), Err(ArithmeticError::Overflow));
+        assert_eq!(d(150, 2).type_name(), "decimal");
+        assert!(d(0, 3).is_zero());
+        assert_eq!(d(25, 1).to_f64(), 2.5);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_cmp_decimal() {
+        let d = |n, scale| Number::from(Decimal::new(n, scale));
+        assert_eq!(d(100, 2), Number::from(1));
+        assert_eq!(d(100, 2), d(1, 0));
+        assert!(d(15, 1) > Number::from(1));
+        assert!(d(-15, 1) < Number::from(-1));
+        assert!(d(15, 1) < Number::from(1.6));
+        assert_eq!(d(5, 1), Number::from(0.5));
+        assert_eq!(d(1, 1), Number::from(0.1));
+        assert!(d(1, 28) > Number::float(1e-30));
+        assert!(d(0, 0) < Number::float(1e-30));
+        assert!(d(0, 0) > Number::float(-1e-30));
+        assert!(d(-1, 28) < Number::float(-1e-30));
+        assert!(d(1, 0) < Number::from(u128::MAX));
+        assert!(d(1, 0) > Number::from(i128::MIN));
+        assert!(d(1, 0) < Number::float(1e30));
+        assert!(d(1, 0) > Number::float(f64::NEG_INFINITY));
+        assert!(d(1, 0).partial_cmp(&Number::float(f64::NAN)).is_none());
+        #[cfg(feature = "bigint")]
+        {
+            let big = Number::from(u128::MAX).checked_mul(&(-4).into()).unwrap();
+            assert!(d(1, 0) > big);
+        }
+    }
+
+    #[cfg(all(feature = "serde", feature = "decimal"))]
+    #[test]
+    fn test_serde_decimal() {
+        let n = Number::from(Decimal::new(150, 2));
+        let s = serde_json::to_string(&n).unwrap();
+        assert_eq!(s, "\"1.50\"");
+        let back: Number = serde_json::from_str(&s).unwrap();
+        assert_eq!(back.to_string(), "1.50");
+        assert!(back.is_decimal());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_128() {
+        let max: Number = u128::MAX.into();
+        let s = serde_json::to_string(&max).unwrap();
+        assert_eq!(s, "\"340282366920938463463374607431768211455\"");
+        let back: Number = serde_json::from_str(&s).unwrap();
+        assert!(matches!(back.n, Num::U128(u128::MAX)));
+        let min: Number = i128::MIN.into();
+        let back: Number = serde_json::from_str(&serde_json::to_string(&min).unwrap()).unwrap();
+        assert!(matches!(back.n, Num::I128(i128::MIN)));
+        let n: Number = serde_json::from_str("-3").unwrap();
+        assert!(matches!(n.n, Num::I(-3)));
+        let n: Number = serde_json::from_str("3.0").unwrap();
+        assert!(n.is_float());
+    }
+
+    #[cfg(all(feature = "serde", feature = "bigint"))]
+    #[test]
+    fn test_serde_bigint() {
+        let big = Number::from(u128::MAX).checked_mul(&4.into()).unwrap();
+        let s = serde_json::to_string(&big).unwrap();
+        assert_eq!(s, "\"1361129467683753853853498429727072845820\"");
+        let back = serde_json::from_str::<Number>(&s).unwrap();
+        assert!(matches!(back.n, Num::Big(_)));
+        assert_eq!(back, big);
+        assert!(serde_json::from_str::<Number>("\"foo\"").is_err());
+    }
 }
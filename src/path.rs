@@ -0,0 +1,344 @@
+//! Lookup of nested `Value`s following Go's field and key chains like
+//! `.Foo.Bar.0`.
+//!
+//! Missing map keys and out of range array indices evaluate to `NoValue`,
+//! which propagates through the rest of the chain. Evaluating a field on a
+//! value that has no fields is an error, reported with the same messages as
+//! Go's `text/template`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::value::{Value, NO_VALUE};
+
+/// Errors returned when evaluating a path.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PathError {
+    #[error("nil pointer evaluating {0}.{1}")]
+    NilPointer(&'static str, String),
+    #[error("can't evaluate field {0} in type {1}")]
+    CantEvaluateField(String, &'static str),
+    #[error("index out of range: {0}")]
+    IndexOutOfRange(usize),
+    #[error("can't append to type {0}")]
+    CantAppend(&'static str),
+    #[error("bad path syntax: {0:?}")]
+    Syntax(String),
+}
+
+/// A parsed chain of fields, keys and indices like `.Foo.Bar.0`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{Value, ValuePath};
+///
+/// let v: Value = vec![vec![1, 2], vec![3, 4]].into();
+/// let path: ValuePath = ".1.0".parse().unwrap();
+/// assert_eq!(path.lookup(&v), Ok(&Value::from(3)));
+/// assert_eq!(path.to_string(), ".1.0");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ValuePath {
+    segments: Vec<String>,
+}
+
+impl ValuePath {
+    /// Creates a path from its segments.
+    pub fn new<S: Into<String>, I: IntoIterator<Item = S>>(segments: I) -> Self {
+        ValuePath {
+            segments: segments.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the segments of the path.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Looks the path up in `val`, see `Value::get_path`.
+    pub fn lookup<'a>(&self, val: &'a Value) -> Result<&'a Value, PathError> {
+        val.get_path(&self.segments)
+    }
+}
+
+/// Parses a path like `.Foo.Bar.0`. `.` is the empty path.
+impl FromStr for ValuePath {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix('.')
+            .ok_or_else(|| PathError::Syntax(s.to_owned()))?;
+        if rest.is_empty() {
+            return Ok(ValuePath::default());
+        }
+        let segments = rest.split('.').map(str::to_owned).collect::<Vec<_>>();
+        if segments.iter().any(String::is_empty) {
+            return Err(PathError::Syntax(s.to_owned()));
+        }
+        Ok(ValuePath { segments })
+    }
+}
+
+impl fmt::Display for ValuePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.segments.is_empty() {
+            return f.write_str(".");
+        }
+        for segment in &self.segments {
+            write!(f, ".{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates a single step of a path.
+fn step<'a>(val: &'a Value, segment: &str) -> Result<&'a Value, PathError> {
+    match *val {
+        Value::NoValue => Ok(val),
+        Value::Nil => Err(PathError::NilPointer("interface {}", segment.to_owned())),
+        Value::Map(ref m) => Ok(m.get(segment).unwrap_or(&NO_VALUE)),
+        Value::Object(ref o) => o
+            .get(segment)
+            .ok_or_else(|| PathError::CantEvaluateField(segment.to_owned(), val.type_name())),
+        Value::Array(ref a) => match segment.parse::<usize>() {
+            Ok(i) => Ok(a.get(i).unwrap_or(&NO_VALUE)),
+            Err(_) => Err(PathError::CantEvaluateField(
+                segment.to_owned(),
+                val.type_name(),
+            )),
+        },
+        _ => Err(PathError::CantEvaluateField(
+            segment.to_owned(),
+            val.type_name(),
+        )),
+    }
+}
+
+/// Evaluates a single step of a path for mutation. `None` means missing.
+fn step_mut<'a>(val: &'a mut Value, segment: &str) -> Result<Option<&'a mut Value>, PathError> {
+    let type_name = val.type_name();
+    match *val {
+        Value::NoValue => Ok(None),
+        Value::Nil => Err(PathError::NilPointer("interface {}", segment.to_owned())),
+        Value::Map(ref mut m) => Ok(Arc::make_mut(m).get_mut(segment)),
+        Value::Object(ref mut o) => match Arc::make_mut(o).get_mut(segment) {
+            Some(v) => Ok(Some(v)),
+            None => Err(PathError::CantEvaluateField(segment.to_owned(), type_name)),
+        },
+        Value::Array(ref mut a) => match segment.parse::<usize>() {
+            Ok(i) => Ok(Arc::make_mut(a).get_mut(i)),
+            Err(_) => Err(PathError::CantEvaluateField(segment.to_owned(), type_name)),
+        },
+        _ => Err(PathError::CantEvaluateField(segment.to_owned(), type_name)),
+    }
+}
+
+/// Evaluates a single step of a path, creating what is missing.
+fn step_or_insert<'a>(val: &'a mut Value, segment: &str) -> Result<&'a mut Value, PathError> {
+    if let Value::NoValue | Value::Nil = *val {
+        *val = Value::Map(Arc::default());
+    }
+    let type_name = val.type_name();
+    match *val {
+        Value::Map(ref mut m) => Ok(Arc::make_mut(m)
+            .entry(segment.to_owned())
+            .or_insert(Value::NoValue)),
+        Value::Array(ref mut a) => match segment.parse::<usize>() {
+            Ok(i) => Arc::make_mut(a)
+                .get_mut(i)
+                .ok_or(PathError::IndexOutOfRange(i)),
+            Err(_) => Err(PathError::CantEvaluateField(segment.to_owned(), type_name)),
+        },
+        _ => step_mut(val, segment)?
+            .ok_or_else(|| PathError::CantEvaluateField(segment.to_owned(), type_name)),
+    }
+}
+
+impl Value {
+    /// Walks `Object` fields, `Map` keys and `Array` indices along `path`.
+    ///
+    /// Missing keys and indices yield `NoValue`. Fields of `Nil`, missing
+    /// `Object` fields and fields of values without any fields are errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use gtmpl_value::Value;
+    ///
+    /// let mut m = HashMap::new();
+    /// m.insert("Foo".to_owned(), vec!["bar"]);
+    /// let v: Value = m.into();
+    ///
+    /// assert_eq!(v.get_path(&["Foo", "0"]), Ok(&Value::from("bar")));
+    /// assert_eq!(v.get_path(&["Baz", "0"]), Ok(&Value::NoValue));
+    /// assert_eq!(
+    ///     v.get_path(&["Foo", "0", "Len"]).unwrap_err().to_string(),
+    ///     "can't evaluate field Len in type string"
+    /// );
+    /// ```
+    pub fn get_path<S: AsRef<str>>(&self, path: &[S]) -> Result<&Value, PathError> {
+        path.iter()
+            .try_fold(self, |val, segment| step(val, segment.as_ref()))
+    }
+
+    /// Mutably walks `Object` fields, `Map` keys and `Array` indices along
+    /// `path`.
+    ///
+    /// Returns `None` if a key or index is missing and fails like `get_path`
+    /// otherwise. Nothing is created, see `ensure_path` for that.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let mut v: Value = vec![vec![1, 2]].into();
+    /// *v.get_path_mut(&["0", "1"]).unwrap().unwrap() = 3.into();
+    /// assert_eq!(v.to_string(), "[[1 3]]");
+    /// assert_eq!(v.get_path_mut(&["1", "0"]), Ok(None));
+    /// ```
+    pub fn get_path_mut<S: AsRef<str>>(
+        &mut self,
+        path: &[S],
+    ) -> Result<Option<&mut Value>, PathError> {
+        let mut val = self;
+        for segment in path {
+            match step_mut(val, segment.as_ref())? {
+                Some(v) => val = v,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(val))
+    }
+
+    /// Mutably walks `path` like `get_path_mut`, creating missing map
+    /// entries on the way.
+    ///
+    /// `NoValue` and `Nil` along the path are replaced by empty `Map`s and a
+    /// missing last entry is inserted as `NoValue`. Array indices must exist
+    /// and `Object` fields can not be added.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let mut v = Value::Nil;
+    /// *v.ensure_path(&["a", "b"]).unwrap() = 1.into();
+    /// assert_eq!(v.to_string(), "map[a:map[b:1]]");
+    /// ```
+    pub fn ensure_path<S: AsRef<str>>(&mut self, path: &[S]) -> Result<&mut Value, PathError> {
+        path.iter()
+            .try_fold(self, |val, segment| step_or_insert(val, segment.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::Map;
+    use std::sync::Arc;
+
+    fn ctx() -> Value {
+        let mut inner = Map::new();
+        inner.insert("Name".to_owned(), "foo".into());
+        inner.insert("Nil".to_owned(), Value::Nil);
+        let mut m = Map::new();
+        m.insert("Obj".to_owned(), Value::Object(Arc::new(inner)));
+        m.insert("List".to_owned(), vec![1, 2, 3].into());
+        Value::Map(Arc::new(m))
+    }
+
+    #[test]
+    fn test_lookup() {
+        let v = ctx();
+        let empty: &[&str] = &[];
+        assert_eq!(v.get_path(empty), Ok(&v));
+        assert_eq!(v.get_path(&["Obj", "Name"]), Ok(&Value::from("foo")));
+        assert_eq!(v.get_path(&["List", "2"]), Ok(&Value::from(3)));
+    }
+
+    #[test]
+    fn test_missing() {
+        let v = ctx();
+        assert_eq!(v.get_path(&["Nope"]), Ok(&Value::NoValue));
+        assert_eq!(v.get_path(&["Nope", "Deeper"]), Ok(&Value::NoValue));
+        assert_eq!(v.get_path(&["List", "3"]), Ok(&Value::NoValue));
+    }
+
+    #[test]
+    fn test_errors() {
+        let v = ctx();
+        assert_eq!(
+            v.get_path(&["Obj", "Age"]),
+            Err(PathError::CantEvaluateField("Age".to_owned(), "struct"))
+        );
+        assert_eq!(
+            v.get_path(&["List", "x"]).unwrap_err().to_string(),
+            "can't evaluate field x in type []interface {}"
+        );
+        assert_eq!(
+            v.get_path(&["Obj", "Nil", "Foo"]).unwrap_err().to_string(),
+            "nil pointer evaluating interface {}.Foo"
+        );
+        assert_eq!(
+            v.get_path(&["List", "0", "Foo"]).unwrap_err().to_string(),
+            "can't evaluate field Foo in type int"
+        );
+    }
+
+    #[test]
+    fn test_lookup_mut() {
+        let mut v = ctx();
+        let shared = v.clone();
+        *v.get_path_mut(&["Obj", "Name"]).unwrap().unwrap() = "bar".into();
+        assert_eq!(v.get_path(&["Obj", "Name"]), Ok(&Value::from("bar")));
+        assert_eq!(shared.get_path(&["Obj", "Name"]), Ok(&Value::from("foo")));
+        assert_eq!(v.get_path_mut(&["Nope", "Deeper"]), Ok(None));
+        assert_eq!(v.get_path_mut(&["List", "3"]), Ok(None));
+        assert_eq!(
+            v.get_path_mut(&["Obj", "Age"]),
+            Err(PathError::CantEvaluateField("Age".to_owned(), "struct"))
+        );
+    }
+
+    #[test]
+    fn test_ensure_path() {
+        let mut v = ctx();
+        *v.ensure_path(&["New", "Deeper"]).unwrap() = 1.into();
+        *v.ensure_path(&["Obj", "Nil", "Foo"]).unwrap() = 2.into();
+        *v.ensure_path(&["List", "0"]).unwrap() = 3.into();
+        assert_eq!(v.get_path(&["New", "Deeper"]), Ok(&Value::from(1)));
+        assert_eq!(v.get_path(&["Obj", "Nil", "Foo"]), Ok(&Value::from(2)));
+        assert_eq!(v.get_path(&["List", "0"]), Ok(&Value::from(3)));
+        assert_eq!(
+            v.ensure_path(&["List", "3"]),
+            Err(PathError::IndexOutOfRange(3))
+        );
+        assert_eq!(
+            v.ensure_path(&["Obj", "Age"]),
+            Err(PathError::CantEvaluateField("Age".to_owned(), "struct"))
+        );
+        assert_eq!(
+            v.ensure_path(&["List", "0", "Foo"]),
+            Err(PathError::CantEvaluateField("Foo".to_owned(), "int"))
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let p: ValuePath = ".Obj.Name".parse().unwrap();
+        assert_eq!(p, ValuePath::new(vec!["Obj", "Name"]));
+        assert_eq!(p.lookup(&ctx()), Ok(&Value::from("foo")));
+        assert_eq!(".".parse::<ValuePath>(), Ok(ValuePath::default()));
+        assert_eq!(ValuePath::default().to_string(), ".");
+        assert!("Obj".parse::<ValuePath>().is_err());
+        assert!(".Obj..Name".parse::<ValuePath>().is_err());
+        assert!(".Obj.".parse::<ValuePath>().is_err());
+    }
+}
@@ -0,0 +1,564 @@
+//! Serialization of any `serde::Serialize` type into a `Value`.
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use serde::ser::{self, Serialize};
+use thiserror::Error;
+
+use crate::number::NUMBER_TOKEN;
+use crate::value::{Map, Number, Value};
+
+/// Errors returned when converting between `Value` and serde types.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SerdeError {
+    #[error("{0}")]
+    Custom(String),
+    #[error("map key must be a string, found {0}")]
+    KeyMustBeAString(&'static str),
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Custom(msg.to_string())
+    }
+}
+
+/// Serializes `T` into a `Value`.
+///
+/// Structs become `Value::Object`, maps `Value::Map` and sequences and tuples
+/// `Value::Array`. `None` and `()` become `Value::Nil`. Unit enum variants are
+/// serialized as their name, other variants as a `Value::Map` with the variant
+/// name as the only key. Bytes become an array of numbers.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{to_value, Value};
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     bar: Vec<u8>,
+/// }
+///
+/// let val = to_value(&Foo { bar: vec![1, 2] }).unwrap();
+/// assert_eq!(val.to_string(), "{[1 2]}");
+/// ```
+pub fn to_value<T>(value: &T) -> Result<Value, SerdeError>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(Serializer)
+}
+
+/// Serializer producing `Value`s.
+pub struct Serializer;
+
+fn variant_map(variant: &'static str, val: Value) -> Value {
+    let mut m = Map::new();
+    m.insert(variant.to_owned(), val);
+    Value::Map(Arc::new(m))
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        Ok(Value::String(v.to_string().into()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value, SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SerdeError> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        Ok(variant.into())
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value, SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let val = value.serialize(self)?;
+        if name == NUMBER_TOKEN {
+            return number(val);
+        }
+        Ok(val)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(variant_map(variant, to_value(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            vec: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            vec: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap {
+            map: Map::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            map: Map::new(),
+            variant: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            map: Map::new(),
+            variant: Some(variant),
+        })
+    }
+}
+
+/// Turns the string a `Number` without a native serde representation is
+/// serialized as back into a `Number`.
+fn number(val: Value) -> Result<Value, SerdeError> {
+    val.as_str()
+        .and_then(Number::from_serialized)
+        .map(Value::Number)
+        .ok_or_else(|| SerdeError::Custom(format!("invalid number {}", val)))
+}
+
+#[doc(hidden)]
+pub struct SerializeArray {
+    vec: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeArray {
+    fn push<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.vec.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, SerdeError> {
+        let array = Value::Array(Arc::new(self.vec));
+        Ok(match self.variant {
+            Some(variant) => variant_map(variant, array),
+            None => array,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeMap {
+    map: Map,
+    next_key: Option<String>,
+}
+
+fn map_key(key: Value) -> Result<String, SerdeError> {
+    match key {
+        Value::String(s) => Ok(s.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        key => Err(SerdeError::KeyMustBeAString(key.type_name())),
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.next_key = Some(map_key(to_value(key)?)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ser::Error::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(Value::Map(Arc::new(self.map)))
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeObject {
+    map: Map,
+    variant: Option<&'static str>,
+}
+
+impl SerializeObject {
+    fn insert<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.map.insert(key.to_owned(), to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, SerdeError> {
+        let object = Value::Object(Arc::new(self.map));
+        Ok(match self.variant {
+            Some(variant) => variant_map(variant, object),
+            None => object,
+        })
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Serialize;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Serialize)]
+    struct Unit;
+
+    #[derive(Serialize)]
+    struct Newtype(i32);
+
+    #[derive(Serialize)]
+    enum E {
+        A,
+        B(u8),
+        C(u8, u8),
+        D { x: bool },
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        name: &'static str,
+        tags: Vec<&'static str>,
+        opt: Option<u8>,
+        e: E,
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(to_value(&true), Ok(Value::Bool(true)));
+        assert_eq!(to_value(&-3i8), Ok((-3).into()));
+        assert_eq!(to_value(&2.5f32), Ok(2.5.into()));
+        assert_eq!(to_value(&'x'), Ok("x".into()));
+        assert_eq!(to_value("foo"), Ok("foo".into()));
+        assert_eq!(to_value(&()), Ok(Value::Nil));
+        assert_eq!(to_value(&Unit), Ok(Value::Nil));
+        assert_eq!(to_value(&Newtype(3)), Ok(3.into()));
+        assert_eq!(to_value(&None::<u8>), Ok(Value::Nil));
+        assert_eq!(to_value(&Some(1u8)), Ok(1.into()));
+        assert_eq!(
+            to_value(&(1, "a")),
+            Ok(vec![Value::from(1), "a".into()].into())
+        );
+    }
+
+    #[test]
+    fn test_128() {
+        assert_eq!(to_value(&(u64::MAX as u128)), Ok(u64::MAX.into()));
+        assert_eq!(to_value(&(i64::MIN as i128)), Ok(i64::MIN.into()));
+        assert_eq!(to_value(&u128::MAX), Ok(u128::MAX.into()));
+        assert_eq!(to_value(&i128::MIN), Ok(i128::MIN.into()));
+        let max = Value::from(u128::MAX);
+        let n = max.as_number().unwrap();
+        assert_eq!(to_value(n), Ok(max.clone()));
+        assert_eq!(
+            to_value(&max).map(|v| format!("{:?}", v)),
+            Ok(format!("{:?}", max))
+        );
+    }
+
+    #[test]
+    fn test_bytes() {
+        use serde::Serializer as _;
+        assert_eq!(Serializer.serialize_bytes(&[1, 2]), Ok(vec![1, 2].into()));
+    }
+
+    #[test]
+    fn test_enum() {
+        assert_eq!(to_value(&E::A), Ok("A".into()));
+        assert_eq!(to_value(&E::B(1)).unwrap().to_string(), "map[B:1]");
+        assert_eq!(to_value(&E::C(1, 2)).unwrap().to_string(), "map[C:[1 2]]");
+        let d = to_value(&E::D { x: true }).unwrap();
+        match d {
+            Value::Map(ref m) => assert!(matches!(m.get("D"), Some(Value::Object(_)))),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_struct() {
+        let foo = Foo {
+            name: "foo",
+            tags: vec!["a", "b"],
+            opt: None,
+            e: E::A,
+        };
+        let val = to_value(&foo).unwrap();
+        match val {
+            Value::Object(ref o) => {
+                assert_eq!(o.get("name"), Some(&"foo".into()));
+                assert_eq!(o.get("tags"), Some(&vec!["a", "b"].into()));
+                assert_eq!(o.get("opt"), Some(&Value::Nil));
+                assert_eq!(o.get("e"), Some(&"A".into()));
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let mut m = HashMap::new();
+        m.insert(2, "b");
+        m.insert(1, "a");
+        assert_eq!(to_value(&m).unwrap().to_string(), "map[1:a 2:b]");
+        let mut m = BTreeMap::new();
+        m.insert(vec![1], 1);
+        assert_eq!(
+            to_value(&m),
+            Err(SerdeError::KeyMustBeAString("[]interface {}"))
+        );
+    }
+}
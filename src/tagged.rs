@@ -0,0 +1,252 @@
+//! Lossless, tagged serde representation of `Value`.
+//!
+//! The default (untagged) representation of `Value` maps directly to the data
+//! model of formats like JSON, which can not tell `NoValue` from `Nil` or
+//! `Object` from `Map`. `TaggedValue` serializes every `Value` as an externally
+//! tagged enum instead, so all variants except `Function` round-trip.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::value::{Map, Number, Value};
+
+/// Owned `Value` using the tagged serde representation.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::{TaggedValue, Value};
+///
+/// let json = serde_json::to_string(&TaggedValue(Value::NoValue)).unwrap();
+/// assert_eq!(json, r#""NoValue""#);
+/// let back: TaggedValue = serde_json::from_str(&json).unwrap();
+/// assert_eq!(back.0, Value::NoValue);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaggedValue(pub Value);
+
+/// Borrowed `Value` using the tagged serde representation.
+///
+/// Allows serializing a `Value` without cloning it into a `TaggedValue`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TaggedRef<'a>(pub &'a Value);
+
+impl From<Value> for TaggedValue {
+    fn from(val: Value) -> Self {
+        TaggedValue(val)
+    }
+}
+
+impl From<TaggedValue> for Value {
+    fn from(val: TaggedValue) -> Self {
+        val.0
+    }
+}
+
+struct TaggedMap<'a>(&'a Map);
+
+impl<'a> Serialize for TaggedMap<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (k, v) in self.0 {
+            map.serialize_entry(k, &TaggedRef(v))?;
+        }
+        map.end()
+    }
+}
+
+struct TaggedArray<'a>(&'a [Value]);
+
+impl<'a> Serialize for TaggedArray<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for v in self.0 {
+            seq.serialize_element(&TaggedRef(v))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'a> Serialize for TaggedRef<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self.0 {
+            Value::NoValue => serializer.serialize_unit_variant("Value", 0, "NoValue"),
+            Value::Nil => serializer.serialize_unit_variant("Value", 1, "Nil"),
+            Value::Bool(ref b) => serializer.serialize_newtype_variant("Value", 2, "Bool", b),
+            Value::String(ref s) => serializer.serialize_newtype_variant("Value", 3, "String", s),
+            Value::Object(ref o) => {
+                serializer.serialize_newtype_variant("Value", 4, "Object", &TaggedMap(o))
+            }
+            Value::Map(ref m) => {
+                serializer.serialize_newtype_variant("Value", 5, "Map", &TaggedMap(m))
+            }
+            Value::Array(ref a) => {
+                serializer.serialize_newtype_variant("Value", 6, "Array", &TaggedArray(a))
+            }
+            Value::Function(ref f) => Err(S::Error::custom(format!(
+                "unable to serialize function {}",
+                f.name()
+            ))),
+            Value::Number(ref n) => serializer.serialize_newtype_variant("Value", 8, "Number", n),
+        }
+    }
+}
+
+impl Serialize for TaggedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        TaggedRef(&self.0).serialize(serializer)
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "Value")]
+enum Tagged {
+    NoValue,
+    Nil,
+    Bool(bool),
+    String(String),
+    Object(BTreeMap<String, TaggedValue>),
+    Map(BTreeMap<String, TaggedValue>),
+    Array(Vec<TaggedValue>),
+    Number(Number),
+}
+
+fn untag_map(m: BTreeMap<String, TaggedValue>) -> Map {
+    m.into_iter().map(|(k, v)| (k, v.0)).collect()
+}
+
+impl<'de> Deserialize<'de> for TaggedValue {
+    fn deserialize<D>(deserializer: D) -> Result<TaggedValue, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = match Tagged::deserialize(deserializer)? {
+            Tagged::NoValue => Value::NoValue,
+            Tagged::Nil => Value::Nil,
+            Tagged::Bool(b) => Value::Bool(b),
+            Tagged::String(s) => Value::String(s.into()),
+            Tagged::Object(o) => Value::Object(Arc::new(untag_map(o))),
+            Tagged::Map(m) => Value::Map(Arc::new(untag_map(m))),
+            Tagged::Array(a) => Value::Array(Arc::new(a.into_iter().map(|v| v.0).collect())),
+            Tagged::Number(n) => Value::Number(n),
+        };
+        Ok(TaggedValue(val))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::{map, Func, FuncError};
+
+    fn round_trip(val: Value) {
+        let json = serde_json::to_string(&TaggedRef(&val)).unwrap();
+        let back: TaggedValue = serde_json::from_str(&json).unwrap();
+        // `Debug` tells numbers of equal value but different kinds apart.
+        assert_eq!(format!("{:?}", back.0), format!("{:?}", val), "{}", json);
+    }
+
+    /// Numbers without a native serde type come back from the untagged
+    /// representation as strings.
+    fn untagged_as_string(val: Value) {
+        let json = serde_json::to_string(&val).unwrap();
+        assert_eq!(json, format!("{:?}", val.to_string()));
+        let back: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, Value::from(val.to_string()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(Value::NoValue);
+        round_trip(Value::Nil);
+        round_trip(true.into());
+        round_trip("foo".into());
+        round_trip(23.into());
+        round_trip((-23).into());
+        round_trip(u64::MAX.into());
+        round_trip(u128::MAX.into());
+        round_trip(i128::MIN.into());
+        round_trip(23.42.into());
+        round_trip(vec![Value::Nil, Value::NoValue].into());
+        round_trip(Value::Object(Arc::new(map(&[
+            ("a", Value::NoValue),
+            ("b", Value::Map(Arc::new(map(&[("c", Value::Nil)])))),
+        ]))));
+    }
+
+    #[test]
+    fn test_format() {
+        let val = Value::Object(Arc::new(map(&[("a", Value::Nil), ("b", vec![1].into())])));
+        let json = serde_json::to_string(&TaggedRef(&val)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Object":{"a":"Nil","b":{"Array":[{"Number":1}]}}}"#
+        );
+    }
+
+    #[test]
+    fn test_function() {
+        fn f(_: &[Value]) -> Result<Value, FuncError> {
+            Ok(Value::Nil)
+        }
+        let val: Value = vec![Value::from(f as Func)].into();
+        assert!(serde_json::to_string(&TaggedRef(&val)).is_err());
+    }
+
+    #[test]
+    fn test_untagged_wide_numbers() {
+        untagged_as_string(u128::MAX.into());
+        untagged_as_string(i128::MIN.into());
+        let json = serde_json::to_string(&Value::from(u128::MAX)).unwrap();
+        assert_eq!(json, r#""340282366920938463463374607431768211455""#);
+        assert_eq!(
+            serde_json::to_string(Value::from(u128::MAX).as_number().unwrap()).unwrap(),
+            json
+        );
+        let m = Value::Map(Arc::new(map(&[(
+            "$gtmpl_value::private::Number",
+            "1".into(),
+        )])));
+        let back: Value = serde_json::from_str(&serde_json::to_string(&m).unwrap()).unwrap();
+        assert_eq!(back, m);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal() {
+        let val: Value = rust_decimal::Decimal::new(150, 2).into();
+        round_trip(val.clone());
+        untagged_as_string(val);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_bigint() {
+        let big = Number::from(u128::MAX).checked_mul(&(-4).into()).unwrap();
+        round_trip(Value::Number(big.clone()));
+        untagged_as_string(Value::Number(big));
+    }
+
+    #[test]
+    fn test_untagged_object() {
+        let val = Value::Object(Arc::new(map(&[("a", 1.into())])));
+        assert_eq!(serde_json::to_string(&val).unwrap(), r#"{"a":1}"#);
+        let back: Value = serde_json::from_str(r#"{"a":1}"#).unwrap();
+        assert_eq!(back, Value::Map(Arc::new(map(&[("a", 1.into())]))));
+    }
+}
@@ -0,0 +1,152 @@
+//! Truthiness of `Value`s following Go's `template.IsTrue`.
+
+use crate::value::Value;
+
+/// Result of evaluating the truthiness of a `Value`.
+///
+/// Go's `template.IsTrue` returns a second boolean telling whether the value
+/// has a meaningful truth value at all. `Truth::Undecided` represents that
+/// case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Truth {
+    /// The value is truthy.
+    True,
+    /// The value is not truthy.
+    False,
+    /// The truthiness can not be decided (e.g. `Value::NoValue`).
+    Undecided,
+}
+
+impl Truth {
+    /// Returns `true` only for `Truth::True`.
+    pub fn is_true(self) -> bool {
+        self == Truth::True
+    }
+
+    /// Returns `None` for `Truth::Undecided` and the truth value otherwise.
+    pub fn decided(self) -> Option<bool> {
+        match self {
+            Truth::True => Some(true),
+            Truth::False => Some(false),
+            Truth::Undecided => None,
+        }
+    }
+}
+
+impl From<bool> for Truth {
+    fn from(b: bool) -> Self {
+        if b {
+            Truth::True
+        } else {
+            Truth::False
+        }
+    }
+}
+
+impl Value {
+    /// Evaluates the truthiness of the `Value` like Go's `template.IsTrue`.
+    ///
+    /// `false`, `0`, `nil` and empty strings, arrays and maps are not truthy.
+    /// Objects and functions are always truthy. `NoValue` is `Undecided`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::{Truth, Value};
+    ///
+    /// assert_eq!(Value::from(1).truth(), Truth::True);
+    /// assert_eq!(Value::from("").truth(), Truth::False);
+    /// assert_eq!(Value::NoValue.truth(), Truth::Undecided);
+    /// ```
+    pub fn truth(&self) -> Truth {
+        match *self {
+            Value::NoValue => Truth::Undecided,
+            Value::Nil => Truth::False,
+            Value::Bool(b) => b.into(),
+            Value::String(ref s) => (!s.is_empty()).into(),
+            Value::Array(ref a) => (!a.is_empty()).into(),
+            Value::Map(ref m) => (!m.is_empty()).into(),
+            Value::Object(_) | Value::Function(_) => Truth::True,
+            Value::Number(ref n) => (!n.is_zero()).into(),
+        }
+    }
+
+    /// Returns `true` if the `Value` is truthy.
+    ///
+    /// Values with `Truth::Undecided` are not truthy.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// assert!(Value::from(vec![1]).is_truthy());
+    /// assert!(!Value::from(0.0).is_truthy());
+    /// assert!(!Value::NoValue.is_truthy());
+    /// ```
+    pub fn is_truthy(&self) -> bool {
+        self.truth().is_true()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::{Func, FuncError, Map};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(Value::NoValue.truth(), Truth::Undecided);
+        assert_eq!(Value::Nil.truth(), Truth::False);
+        assert_eq!(Value::from(true).truth(), Truth::True);
+        assert_eq!(Value::from(false).truth(), Truth::False);
+        assert_eq!(Value::from("a").truth(), Truth::True);
+        assert_eq!(Value::from("").truth(), Truth::False);
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(Value::from(0u64).truth(), Truth::False);
+        assert_eq!(Value::from(0i64).truth(), Truth::False);
+        assert_eq!(Value::from(0.0f64).truth(), Truth::False);
+        assert_eq!(Value::from(-0.0f64).truth(), Truth::False);
+        assert_eq!(Value::from(1u8).truth(), Truth::True);
+        assert_eq!(Value::from(-1i8).truth(), Truth::True);
+        assert_eq!(Value::from(0.1f64).truth(), Truth::True);
+        assert_eq!(Value::from(-0.1f32).truth(), Truth::True);
+        // Go: NaN != 0 is true.
+        assert_eq!(Value::from(f64::NAN).truth(), Truth::True);
+        assert_eq!(Value::from(f64::INFINITY).truth(), Truth::True);
+    }
+
+    #[test]
+    fn test_collections() {
+        assert_eq!(Value::Array(Arc::new(vec![])).truth(), Truth::False);
+        assert_eq!(Value::from(vec![Value::Nil]).truth(), Truth::True);
+        assert_eq!(Value::Map(Arc::new(Map::new())).truth(), Truth::False);
+        let mut m = Map::new();
+        m.insert("a".to_owned(), Value::from(false));
+        assert_eq!(Value::Map(Arc::new(m)).truth(), Truth::True);
+        // Go: struct values are always true.
+        assert_eq!(Value::Object(Arc::new(Map::new())).truth(), Truth::True);
+    }
+
+    #[test]
+    fn test_function() {
+        fn f(_: &[Value]) -> Result<Value, FuncError> {
+            Ok(Value::Nil)
+        }
+        assert_eq!(Value::from(f as Func).truth(), Truth::True);
+    }
+
+    #[test]
+    fn test_truth() {
+        assert!(Truth::True.is_true());
+        assert!(!Truth::False.is_true());
+        assert!(!Truth::Undecided.is_true());
+        assert_eq!(Truth::True.decided(), Some(true));
+        assert_eq!(Truth::False.decided(), Some(false));
+        assert_eq!(Truth::Undecided.decided(), None);
+    }
+}
@@ -1,10 +1,21 @@
 use std::cmp::PartialEq;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::sync::Arc;
 use thiserror::Error;
 
+use crate::compare::CompareError;
+use crate::convert::ConversionError;
+use crate::format;
+use crate::literal::LiteralError;
+use crate::path::PathError;
+#[cfg(feature = "serde")]
+use crate::ser::SerdeError;
+
+#[doc(inline)]
+pub use crate::function::{Arity, DynFunc, Func, Function};
 #[doc(inline)]
-pub use crate::number::Number;
+pub use crate::number::{ArithmeticError, Number};
 
 #[derive(Debug, Error)]
 pub enum FuncError {
@@ -17,37 +28,45 @@ pub enum FuncError {
     #[error("{0}")]
     Generic(String),
     #[error(transparent)]
+    Conversion(#[from] ConversionError),
+    #[error(transparent)]
+    Compare(#[from] CompareError),
+    #[error(transparent)]
+    Path(#[from] PathError),
+    #[error(transparent)]
+    Literal(#[from] LiteralError),
+    #[error(transparent)]
+    Arithmetic(#[from] ArithmeticError),
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Serde(#[from] SerdeError),
+    #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-/// Function type supported by `gtmpl_value`.
-pub type Func = fn(&[Value]) -> Result<Value, FuncError>;
+/// Map type used by `Value::Object` and `Value::Map`.
+///
+/// Entries are ordered by key, so iterating a `Map` yields the same order as
+/// Go's `range` over a map and output is deterministic.
+pub type Map = BTreeMap<String, Value>;
 
-/// Wrapper struct for `Func`.
-#[derive(Clone)]
-pub struct Function {
-    pub f: Func,
-}
+/// Shared `NoValue` returned by lookups that miss.
+pub(crate) static NO_VALUE: Value = Value::NoValue;
 
-impl PartialEq for Function {
-    fn eq(&self, other: &Function) -> bool {
-        self.f as fn(_) -> _ == other.f as fn(_) -> _
-    }
-}
-
-impl fmt::Debug for Function {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Function")
-    }
-}
-
-impl fmt::Display for Function {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Function")
-    }
+/// Builds a `Map` from borrowed entries, shared by the tests.
+#[cfg(test)]
+pub(crate) fn map(entries: &[(&str, Value)]) -> Map {
+    entries
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect()
 }
 
 /// Represents a gtmpl value.
+///
+/// Strings and collections are reference counted and immutable while shared,
+/// so cloning a `Value` is O(1). `as_array_mut` and `as_map_mut` copy the
+/// underlying collection on write if it is shared.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(untagged))]
@@ -55,11 +74,11 @@ pub enum Value {
     NoValue,
     Nil,
     Bool(bool),
-    String(String),
-    #[cfg_attr(feature = "serde", serde(skip))]
-    Object(HashMap<String, Value>),
-    Map(HashMap<String, Value>),
-    Array(Vec<Value>),
+    String(Arc<str>),
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Object(Arc<Map>),
+    Map(Arc<Map>),
+    Array(Arc<Vec<Value>>),
     #[cfg_attr(feature = "serde", serde(skip))]
     Function(Function),
     Number(Number),
@@ -72,20 +91,134 @@ impl Value {
     {
         t.into()
     }
+
+    /// Returns a Go like name of the `Value`'s type, used in error messages.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// assert_eq!(Value::from("foo").type_name(), "string");
+    /// assert_eq!(Value::from(vec![1]).type_name(), "[]interface {}");
+    /// ```
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Value::NoValue => "invalid",
+            Value::Nil => "nil",
+            Value::Bool(_) => "bool",
+            Value::String(_) => "string",
+            Value::Object(_) => "struct",
+            Value::Map(_) => "map[string]interface {}",
+            Value::Array(_) => "[]interface {}",
+            Value::Function(_) => "func",
+            Value::Number(ref n) => n.type_name(),
+        }
+    }
+
+    /// Returns the boolean if the `Value` is a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Borrows the string if the `Value` is a `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let v: Value = "foo".into();
+    /// assert_eq!(v.as_str(), Some("foo"));
+    /// assert_eq!(Value::Nil.as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Borrows the number if the `Value` is a `Number`.
+    pub fn as_number(&self) -> Option<&Number> {
+        match *self {
+            Value::Number(ref n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Borrows the elements if the `Value` is an `Array`.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Borrows the entries if the `Value` is a `Map` or an `Object`.
+    pub fn as_map(&self) -> Option<&Map> {
+        match *self {
+            Value::Map(ref m) | Value::Object(ref m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Mutably borrows the elements if the `Value` is an `Array`.
+    ///
+    /// The elements are cloned first if they are shared with another `Value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use gtmpl_value::Value;
+    ///
+    /// let a: Value = vec![1, 2].into();
+    /// let mut b = a.clone();
+    /// b.as_array_mut().unwrap().push(3.into());
+    /// assert_eq!(a.to_string(), "[1 2]");
+    /// assert_eq!(b.to_string(), "[1 2 3]");
+    /// ```
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match *self {
+            Value::Array(ref mut a) => Some(Arc::make_mut(a)),
+            _ => None,
+        }
+    }
+
+    /// Mutably borrows the entries if the `Value` is a `Map` or an `Object`.
+    ///
+    /// The entries are cloned first if they are shared with another `Value`.
+    pub fn as_map_mut(&mut self) -> Option<&mut Map> {
+        match *self {
+            Value::Map(ref mut m) | Value::Object(ref mut m) => Some(Arc::make_mut(m)),
+            _ => None,
+        }
+    }
+
+    /// Borrows the function if the `Value` is a `Function`.
+    pub fn as_function(&self) -> Option<&Function> {
+        match *self {
+            Value::Function(ref f) => Some(f),
+            _ => None,
+        }
+    }
 }
 
+/// Formats the `Value` like Go's `fmt.Sprint`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gtmpl_value::Value;
+///
+/// let v: Value = vec![1, 2, 3].into();
+/// assert_eq!(v.to_string(), "[1 2 3]");
+/// ```
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            Value::NoValue => write!(f, "<no value>"),
-            Value::Nil => write!(f, "nil"),
-            Value::Bool(ref b) => write!(f, "{}", b),
-            Value::String(ref s) => write!(f, "{}", s),
-            Value::Function(ref func) => write!(f, "{}", func),
-            Value::Number(ref n) => write!(f, "{}", n),
-            Value::Array(ref a) => write!(f, "{:?}", a),
-            Value::Object(ref o) => write!(f, "{:?}", o),
-            Value::Map(ref m) => write!(f, "{:?}", m),
-        }
+        format::write_value(f, self)
     }
 }